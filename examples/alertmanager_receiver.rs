@@ -0,0 +1,48 @@
+/**
+    A standalone Alertmanager webhook receiver, relaying the alerts to Pushover.
+
+    Try this example by running: `cargo run --example alertmanager-receiver --features alertmanager`
+    then point an Alertmanager webhook receiver to http://localhost:9096/
+**/
+extern crate pushover_rs;
+use pushover_rs::alertmanager::AlertmanagerRelay;
+use tiny_http::{Method, Response, Server};
+
+mod common;
+use common::*;
+
+#[tokio::main]
+pub async fn main() {
+    // Reads the credentials from a file, feel free to use anything else to store your own credentials.
+    let credentials: ExampleCredentials = read_credentials();
+    let relay: AlertmanagerRelay = AlertmanagerRelay::new(&credentials.user, &credentials.token);
+    let server: Server = Server::http("0.0.0.0:9096").expect("Cannot listen on port 9096");
+    println!("Listening for Alertmanager notifications on http://0.0.0.0:9096/");
+
+    // tiny_http blocks while waiting for a request, which is fine for such a small receiver
+    for mut request in server.incoming_requests() {
+        if *request.method() != Method::Post {
+            let _ = request.respond(Response::from_string("Only POST is supported").with_status_code(405));
+            continue;
+        }
+
+        let mut body: Vec<u8> = Vec::new();
+        if request.as_reader().read_to_end(&mut body).is_err() {
+            let _ = request.respond(Response::from_string("Cannot read the body").with_status_code(400));
+            continue;
+        }
+
+        let response = match relay.handle(&body).await {
+            Ok(results) => {
+                for (alert, result) in results {
+                    if let Err(error) = result {
+                        eprintln!("Cannot relay alert {}: {}", alert, error);
+                    }
+                }
+                Response::from_string("OK")
+            },
+            Err(error) => Response::from_string(error.to_string()).with_status_code(400),
+        };
+        let _ = request.respond(response);
+    }
+}
@@ -11,7 +11,7 @@ pub struct ExampleCredentials {
 pub fn read_credentials() -> ExampleCredentials {
     let path: String = "examples/data/credentials.ron".into();
     let file: File = File::open(&path)
-        .expect(&format!("Cannot find file at location: {}", path));
+        .unwrap_or_else(|_| panic!("Cannot find file at location: {}", path));
     match from_reader(file) {
         Ok(creds) => creds,
         Err(err) => {
@@ -3,10 +3,9 @@
 
     Try this example by running: `cargo run --example usage-example`
 **/
-
 extern crate pushover_rs;
-use pushover_rs::{Message, MessageBuilder, PushoverResponse, PushoverSound, send_pushover_request};
-use std::time::{SystemTime, UNIX_EPOCH};
+use pushover_rs::{Message, MessageBuilder, Priority, PushoverResponse, PushoverSound, send_pushover_request};
+use std::time::{Duration, SystemTime};
 
 mod common;
 use common::*;
@@ -14,15 +13,13 @@ use common::*;
 async fn something_happened_send_notification() -> Result<PushoverResponse, Box<dyn std::error::Error>> {
     // Reads the credentials from a file, feel free to use anything else to store your own credentials.
     let credentials: ExampleCredentials = read_credentials();
-    let duration_since_epoch = SystemTime::now().duration_since(UNIX_EPOCH)?;
-    let now: u64 = duration_since_epoch.as_secs();
     let message: Message = MessageBuilder::new(&credentials.user, &credentials.token, "Example message")
         .set_title("Example push notification sent through Pushover API")
         .set_url("https://pushover.net/", Some("Pushover"))
-        .set_priority(1)
-        .set_ttl(60) // 60 seconds. Note that TTLs will be ignored if the priority is set to 2. See: https://pushover.net/api#ttl
+        .set_priority(Priority::High)
+        .set_ttl(Duration::from_secs(60)) // Note that TTLs will be ignored if the priority is set to 2. See: https://pushover.net/api#ttl
         .set_sound(PushoverSound::ALIEN)
-        .set_timestamp(now)
+        .set_timestamp(SystemTime::now())
         .build();
     
     send_pushover_request(message).await
@@ -48,9 +45,8 @@ pub async fn main() {
             // Request level error(s)
             eprintln!("The request was incorrect.");
 
-            if pushover_response.errors.is_some() {
-                let errors = pushover_response.errors
-                    .unwrap()
+            if let Some(errors) = pushover_response.errors {
+                let errors = errors
                     .into_iter()
                     .map(|x| format!("{} ", x))
                     .collect::<String>();
@@ -10,18 +10,16 @@ extern crate pushover_rs;
 mod common;
 use common::*;
 
-use std::time::{SystemTime, UNIX_EPOCH, Duration};
+use std::time::SystemTime;
 use pushover_rs::{AttachmentMessageBuilder, send_pushover_request_with_attachment, PushoverResponse, PushoverSound};
 
 pub fn send_pushover_message_with_attachment() -> Result<PushoverResponse, Box<dyn std::error::Error>> {
     let credentials: ExampleCredentials = read_credentials();
-    let duration_since_epoch: Duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let now: u64 = duration_since_epoch.as_secs();
     let message = AttachmentMessageBuilder::new(&credentials.user, &credentials.token, "Some message")
         .set_title("Example with attachment")
         .set_attachment("examples/data/attachment.jpg".into())
         .set_sound(PushoverSound::TUGBOAT)
-        .set_timestamp(now)
+        .set_timestamp(SystemTime::now())
         .build()
         .unwrap();
     send_pushover_request_with_attachment(message)
@@ -40,9 +38,8 @@ pub fn main() {
             // Request level error(s)
             eprintln!("The request was incorrect.");
 
-            if pushover_response.errors.is_some() {
-                let errors = pushover_response.errors
-                    .unwrap()
+            if let Some(errors) = pushover_response.errors {
+                let errors = errors
                     .into_iter()
                     .map(|x| format!("{} ", x))
                     .collect::<String>();
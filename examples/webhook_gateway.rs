@@ -0,0 +1,44 @@
+/**
+    A standalone webhook gateway, relaying JSON webhooks to Pushover following mapping rules.
+
+    Copy `examples/data/webhook_gateway_example.toml` to `examples/data/webhook_gateway.toml` and fill it in,
+    then try this example by running: `cargo run --example webhook-gateway --features webhook,toml`
+    and point your webhooks to http://localhost:9097/[route path]
+**/
+extern crate pushover_rs;
+use pushover_rs::webhook::WebhookGateway;
+use tiny_http::{Method, Response, Server};
+
+#[tokio::main]
+pub async fn main() {
+    let path: String = "examples/data/webhook_gateway.toml".into();
+    let config: String = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Cannot find file at location: {}", path));
+    let gateway: WebhookGateway = WebhookGateway::from_toml_str(&config).expect("Invalid gateway configuration");
+    let server: Server = Server::http("0.0.0.0:9097").expect("Cannot listen on port 9097");
+    println!("Listening for webhooks on http://0.0.0.0:9097/");
+
+    // tiny_http blocks while waiting for a request, which is fine for such a small gateway
+    for mut request in server.incoming_requests() {
+        if *request.method() != Method::Post {
+            let _ = request.respond(Response::from_string("Only POST is supported").with_status_code(405));
+            continue;
+        }
+
+        let path: String = request.url().split('?').next().unwrap_or_default().to_owned();
+        let mut body: Vec<u8> = Vec::new();
+        if request.as_reader().read_to_end(&mut body).is_err() {
+            let _ = request.respond(Response::from_string("Cannot read the body").with_status_code(400));
+            continue;
+        }
+
+        let response = match gateway.handle(&path, &body).await {
+            Ok(_) => Response::from_string("OK"),
+            Err(error) => {
+                eprintln!("Cannot relay the webhook received on {}: {}", path, error);
+                Response::from_string(error.to_string()).with_status_code(400)
+            },
+        };
+        let _ = request.respond(response);
+    }
+}
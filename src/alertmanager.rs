@@ -0,0 +1,254 @@
+/*!
+Relays Prometheus Alertmanager webhook notifications to Pushover (Requires the `alertmanager` feature)
+
+The relay doesn't include an HTTP server: give it the body of the webhook requests received by yours
+(see the `alertmanager_receiver` example for a standalone receiver).
+
+Alerts are mapped to priorities and sounds by their severity label. The receipts of emergency alerts are kept,
+so that their retries are cancelled once the alert is resolved.
+ */
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+pub use crate::pushover::data::{Alert, AlertStatus, AlertmanagerPayload};
+use crate::{Message, MessageBuilder, MessageTemplate, Priority, PushoverClient, PushoverError, PushoverResponse, PushoverSound};
+
+/**
+Sends the alerts of Alertmanager notifications as Pushover messages.
+
+By default, alerts are routed on their "severity" label: "critical" alerts are emergencies (priority 2, siren sound),
+"error" and "warning" ones have a high priority (1), "info" ones a low priority (-1), and the others a normal priority.
+Resolved alerts are notified with at most a normal priority.
+
+Without a template, the title is `[FIRING] <alertname>` and the message is made of the summary and description annotations.
+ */
+#[derive(Debug)]
+pub struct AlertmanagerRelay {
+    user_key: String,
+    app_token: String,
+    severity_label: String,
+    severities: HashMap<String, SeverityRoute>,
+    default_route: SeverityRoute,
+    template: Option<MessageTemplate>,
+    send_resolved: bool,
+    client: PushoverClient,
+    /// The receipts of the firing emergency alerts, by alert key
+    receipts: Mutex<HashMap<String, TrackedReceipt>>,
+}
+
+/// The receipt of a firing emergency alert, kept until its retries stop
+#[derive(Debug, Clone)]
+struct TrackedReceipt {
+    receipt: String,
+    expires_at: SystemTime,
+}
+
+/**
+The priority and sound of the alerts of a severity.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeverityRoute {
+    /// The priority of the firing alerts, the resolved ones having at most a normal priority
+    pub priority: Priority,
+    /// The name of a Pushover sound, otherwise the user's default sound is used
+    pub sound: Option<String>,
+}
+
+impl SeverityRoute {
+    pub fn new(priority: Priority, sound: Option<PushoverSound>) -> Self {
+        Self {
+            priority,
+            sound: sound.map(|sound| sound.to_string()),
+        }
+    }
+}
+
+impl AlertmanagerRelay {
+    /// Creates a relay sending the alerts to this user or group
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        Self {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            severity_label: "severity".to_owned(),
+            severities: HashMap::from([
+                ("critical".to_owned(), SeverityRoute::new(Priority::emergency(), Some(PushoverSound::SIREN))),
+                ("error".to_owned(), SeverityRoute::new(Priority::High, None)),
+                ("warning".to_owned(), SeverityRoute::new(Priority::High, None)),
+                ("info".to_owned(), SeverityRoute::new(Priority::Low, None)),
+            ]),
+            default_route: SeverityRoute::new(Priority::Normal, None),
+            template: None,
+            send_resolved: true,
+            client: PushoverClient::new(),
+            receipts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sends the messages through this client
+    pub fn set_client(mut self, client: PushoverClient) -> AlertmanagerRelay {
+        self.client = client;
+        self
+    }
+
+    /// Sets the label holding the severity of the alerts (Default: "severity")
+    pub fn set_severity_label(mut self, label: &str) -> AlertmanagerRelay {
+        self.severity_label = label.to_owned();
+        self
+    }
+
+    /// Sets the priority and sound of the alerts of a severity (compared case-insensitively)
+    pub fn set_severity(mut self, severity: &str, route: SeverityRoute) -> AlertmanagerRelay {
+        self.severities.insert(severity.to_lowercase(), route);
+        self
+    }
+
+    /// Sets the priority and sound of the alerts without a known severity
+    pub fn set_default_severity(mut self, route: SeverityRoute) -> AlertmanagerRelay {
+        self.default_route = route;
+        self
+    }
+
+    /// Renders the messages with a template, against the alert (eg: "{labels.instance}", "{annotations.summary}", "{status}").
+    ///
+    /// The priority and sound still come from the severity.
+    pub fn set_template(mut self, template: MessageTemplate) -> AlertmanagerRelay {
+        self.template = Some(template);
+        self
+    }
+
+    /// Whether resolved alerts are notified (Default: true). Emergency retries are cancelled either way.
+    pub fn set_send_resolved(mut self, send_resolved: bool) -> AlertmanagerRelay {
+        self.send_resolved = send_resolved;
+        self
+    }
+
+    /// Parses the body of a webhook request and relays its alerts, see relay
+    pub async fn handle(&self, body: &[u8]) -> Result<Vec<(String, Result<PushoverResponse, PushoverError>)>, PushoverError> {
+        let payload: AlertmanagerPayload = serde_json::from_slice(body)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid Alertmanager payload: {}", error)))?;
+        Ok(self.relay(&payload).await)
+    }
+
+    /// Relays the alerts of a notification: firing alerts are sent, resolved alerts have their emergency retries
+    /// cancelled and are sent (unless disabled).
+    ///
+    /// Alertmanager sends the firing alerts of a group again whenever the group changes: a firing alert whose
+    /// emergency message is still retried is not sent again. It is sent again once its retries expired,
+    /// or once the user acknowledged it.
+    ///
+    /// Returns the result of each call to the API, with the key of its alert.
+    pub async fn relay(&self, payload: &AlertmanagerPayload) -> Vec<(String, Result<PushoverResponse, PushoverError>)> {
+        self.relay_at(payload, SystemTime::now()).await
+    }
+
+    /// Same as relay, at the given time
+    pub(crate) async fn relay_at(&self, payload: &AlertmanagerPayload, now: SystemTime) -> Vec<(String, Result<PushoverResponse, PushoverError>)> {
+        let mut results: Vec<(String, Result<PushoverResponse, PushoverError>)> = Vec::new();
+        // The retries of these receipts are over, there is nothing left to cancel
+        self.receipts().retain(|_, tracked| tracked.expires_at > now);
+
+        for alert in &payload.alerts {
+            let key: String = alert.key();
+
+            let tracked: Option<TrackedReceipt> = self.receipts().get(&key).cloned();
+            if let (AlertStatus::Firing, Some(tracked)) = (&alert.status, tracked) {
+                // If its status can't be read, the message is assumed to be still retried
+                let still_retried: bool = match self.client.get_receipt(&self.app_token, &tracked.receipt).await {
+                    Ok(status) => !status.is_acknowledged() && status.expired == 0,
+                    Err(_) => true,
+                };
+                if still_retried {
+                    continue;
+                }
+                self.receipts().remove(&key);
+            }
+
+            if alert.status == AlertStatus::Resolved {
+                let tracked: Option<TrackedReceipt> = self.receipts().remove(&key);
+                if let Some(tracked) = tracked {
+                    results.push((key.clone(), self.client.cancel_receipt(&self.app_token, &tracked.receipt).await));
+                }
+
+                if !self.send_resolved {
+                    continue;
+                }
+            }
+
+            let message: Message = match self.message_for(alert) {
+                Ok(message) => message,
+                Err(error) => {
+                    results.push((key, Err(error)));
+                    continue;
+                },
+            };
+            let result: Result<PushoverResponse, PushoverError> = self.client.send(&message).await;
+            if let Ok(PushoverResponse { receipt: Some(receipt), .. }) = &result {
+                if alert.status == AlertStatus::Firing {
+                    let expire: Duration = Duration::from_secs(message.expire.unwrap_or_default().max(0) as u64);
+                    self.receipts().insert(key.clone(), TrackedReceipt { receipt: receipt.clone(), expires_at: now + expire });
+                }
+            }
+            results.push((key, result));
+        }
+
+        results
+    }
+
+    /// Builds the message of an alert
+    pub fn message_for(&self, alert: &Alert) -> Result<Message, PushoverError> {
+        let route: &SeverityRoute = alert.labels
+            .get(&self.severity_label)
+            .and_then(|severity| self.severities.get(&severity.to_lowercase()))
+            .unwrap_or(&self.default_route);
+
+        let builder: MessageBuilder = match &self.template {
+            Some(template) => template.render(&self.user_key, &self.app_token, alert)?,
+            None => self.default_message(alert),
+        };
+
+        let priority: Priority = match alert.status {
+            AlertStatus::Resolved if route.priority.level() > 0 => Priority::Normal,
+            _ => route.priority.clone(),
+        };
+        let mut message: Message = builder.set_priority(priority).build();
+        message.sound = match alert.status {
+            AlertStatus::Firing => route.sound.clone().or(message.sound),
+            AlertStatus::Resolved => message.sound,
+        };
+
+        Ok(message)
+    }
+
+    fn default_message(&self, alert: &Alert) -> MessageBuilder {
+        let name: &str = alert.labels.get("alertname").map_or("Alert", |name| name.as_str());
+        let status: &str = match alert.status {
+            AlertStatus::Firing => "FIRING",
+            AlertStatus::Resolved => "RESOLVED",
+        };
+
+        let text: String = [alert.annotations.get("summary"), alert.annotations.get("description")]
+            .into_iter()
+            .flatten()
+            .map(|annotation| annotation.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n\n");
+        let text: String = if text.is_empty() {
+            format!("{} is {}", name, status.to_lowercase())
+        } else {
+            text
+        };
+
+        let mut builder: MessageBuilder = MessageBuilder::new(&self.user_key, &self.app_token, &text)
+            .set_title(&format!("[{}] {}", status, name));
+        if !alert.generator_url.is_empty() {
+            builder = builder.set_url(&alert.generator_url, Some("Source"));
+        }
+        builder
+    }
+
+    fn receipts(&self) -> MutexGuard<'_, HashMap<String, TrackedReceipt>> {
+        self.receipts.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
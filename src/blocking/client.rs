@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::pushover::client_core::{self, Call, ClientCore};
+#[cfg(feature = "metrics")]
+use crate::pushover::constants::PUSHOVER_API_ENDPOINT;
+#[cfg(feature = "metrics")]
+use crate::pushover::delivery_metrics;
+#[cfg(feature = "tracing")]
+use crate::pushover::instrumentation;
+use crate::transport::{TransportRequest, TransportResponse};
+use crate::pushover::data::{AppLimitsResponse, LicenseAssignee, LicenseOs, ReceiptResponse, TeamResponse, TeamUser, UserValidationResponse};
+use crate::{AttachmentMessage, Deduplication, DeduplicationStats, Message, PreparedRequest, PushoverError, PushoverResponse, Recipients};
+#[cfg(feature = "schedule")]
+use crate::Schedule;
+use super::{ReqwestTransport, Transport};
+
+/**
+A reusable, blocking Pushover client, sharing its HTTP connections between requests.
+
+Bulk sends are limited to a number of concurrent requests (one thread each) and can be paced to a number of requests per second,
+to stay under Pushover's limits. Clones of a client share the same pacing.
+ */
+#[derive(Clone)]
+pub struct PushoverClient {
+    transport: Arc<dyn Transport>,
+    core: ClientCore,
+}
+
+impl Default for PushoverClient {
+    fn default() -> Self {
+        Self {
+            transport: Arc::new(ReqwestTransport::new()),
+            core: ClientCore::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for PushoverClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.core.debug("PushoverClient", f)
+    }
+}
+
+impl PushoverClient {
+    /// Creates a new client, sending at most 2 concurrent requests, without pacing
+    pub fn new() -> Self {
+        PushoverClient::default()
+    }
+
+    /// Sends the requests through another HTTP stack than the default reqwest client
+    pub fn set_transport<T: Transport + 'static>(mut self, transport: T) -> PushoverClient {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Sets the maximum number of requests sent at the same time by send_all. (Min: 1)
+    pub fn set_concurrency(mut self, concurrency: usize) -> PushoverClient {
+        self.core.set_concurrency(concurrency);
+        self
+    }
+
+    /// Paces the requests sent by this client (and its clones) to at most this many per second.
+    ///
+    /// Setting 0 removes the pacing.
+    pub fn set_requests_per_second(mut self, requests_per_second: u32) -> PushoverClient {
+        self.core.set_requests_per_second(requests_per_second);
+        self
+    }
+
+    /// Removes the pacing
+    pub fn remove_requests_per_second(mut self) -> PushoverClient {
+        self.core.set_requests_per_second(0);
+        self
+    }
+
+    /// In dry-run mode, nothing is sent: requests are described and kept (see take_dry_run_requests),
+    /// and every send succeeds with a "dry-run" request GUID.
+    ///
+    /// Requests only reading data (validate_user, get_limits, get_receipt, get_team, get_license_credits) are still sent, as they change nothing.
+    pub fn set_dry_run(mut self, dry_run: bool) -> PushoverClient {
+        self.core.set_dry_run(dry_run);
+        self
+    }
+
+    /// Returns (and forgets) the requests described in dry-run mode, in order
+    pub fn take_dry_run_requests(&self) -> Vec<PreparedRequest> {
+        self.core.take_dry_run_requests()
+    }
+
+    /// Suppresses the duplicates of recently sent messages (see Deduplication), for this client and its clones.
+    ///
+    /// Only messages without attachment are deduplicated. A suppressed message fails with PushoverError::Suppressed.
+    pub fn set_deduplication(mut self, deduplication: Deduplication) -> PushoverClient {
+        self.core.set_deduplication(Some(deduplication));
+        self
+    }
+
+    /// Removes the deduplication
+    pub fn remove_deduplication(mut self) -> PushoverClient {
+        self.core.set_deduplication(None);
+        self
+    }
+
+    /// Returns the counters of the deduplication (all 0 without deduplication)
+    pub fn deduplication_stats(&self) -> DeduplicationStats {
+        self.core.deduplication_stats()
+    }
+
+    /// Applies a business hours policy to the messages without attachment (see Schedule).
+    ///
+    /// Deferred messages are not sent, their send fails with PushoverError::Deferred, telling when the business hours start
+    /// (eg: to hand them to a Scheduler). (Requires the `schedule` feature)
+    #[cfg(feature = "schedule")]
+    pub fn set_schedule(mut self, schedule: Schedule) -> PushoverClient {
+        self.core.set_schedule(Some(schedule));
+        self
+    }
+
+    /// Removes the business hours policy
+    #[cfg(feature = "schedule")]
+    pub fn remove_schedule(mut self) -> PushoverClient {
+        self.core.set_schedule(None);
+        self
+    }
+
+    /// Sends a push notification without attachment.
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "pushover.send", level = "debug", skip_all,
+        fields(user = %instrumentation::redact(&message.user_key), priority = message.priority),
+    ))]
+    pub fn send(&self, message: &Message) -> Result<PushoverResponse, PushoverError> {
+        let result: Result<PushoverResponse, PushoverError> = self.deliver(message);
+
+        #[cfg(feature = "metrics")]
+        if !self.core.is_dry_run() {
+            delivery_metrics::record_send(PUSHOVER_API_ENDPOINT, message.priority, &result);
+        }
+
+        result
+    }
+
+    /// Sends a push notification with attachment.
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    #[cfg_attr(feature = "tracing", tracing::instrument(
+        name = "pushover.send_with_attachment", level = "debug", skip_all,
+        fields(user = %instrumentation::redact(&message.user_key), priority = message.priority, attachment_bytes = tracing::field::Empty),
+    ))]
+    pub fn send_with_attachment(&self, message: &AttachmentMessage) -> Result<PushoverResponse, PushoverError> {
+        let result: Result<PushoverResponse, PushoverError> = self.deliver_with_attachment(message);
+
+        #[cfg(feature = "metrics")]
+        if !self.core.is_dry_run() {
+            delivery_metrics::record_send(PUSHOVER_API_ENDPOINT, message.priority.as_ref(), &result);
+        }
+
+        result
+    }
+
+    /// Stops the retries of an emergency priority message, given the receipt returned when it was sent.
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn cancel_receipt(&self, application_token: &str, receipt: &str) -> Result<PushoverResponse, PushoverError> {
+        self.call(self.core.cancel_receipt(application_token, receipt)?)
+    }
+
+    /// Polls the receipt of an emergency priority message: whether it was acknowledged, delivered, and when its retries stop.
+    ///
+    /// A request refused by the API (status 0, eg: an unknown receipt) is returned as an error.
+    pub fn get_receipt(&self, application_token: &str, receipt: &str) -> Result<ReceiptResponse, PushoverError> {
+        self.call(ClientCore::get_receipt(application_token, receipt))
+    }
+
+    /// Checks that a user or group key is valid (and has active devices), and optionally that it has this device.
+    ///
+    /// A request refused by the API (status 0, eg: an invalid user or device) is returned as an error.
+    pub fn validate_user(&self, application_token: &str, user_key: &str, device: Option<&str>) -> Result<UserValidationResponse, PushoverError> {
+        self.call(ClientCore::validate_user(application_token, user_key, device)?)
+    }
+
+    /// Returns the monthly message limit of the application, and how many messages are left.
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn get_limits(&self, application_token: &str) -> Result<AppLimitsResponse, PushoverError> {
+        self.call(ClientCore::get_limits(application_token))
+    }
+
+    /// Shows the team's name and members, given a team token (see the teams module).
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn get_team(&self, team_token: &str) -> Result<TeamResponse, PushoverError> {
+        self.call(ClientCore::get_team(team_token))
+    }
+
+    /// Adds a user to the team, creating their account if needed (see the teams module).
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn add_team_user(&self, user: &TeamUser) -> Result<PushoverResponse, PushoverError> {
+        self.call(self.core.add_team_user(user)?)
+    }
+
+    /// Removes a user from the team, identified by their e-mail address (see the teams module).
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn remove_team_user(&self, team_token: &str, email: &str) -> Result<PushoverResponse, PushoverError> {
+        self.call(self.core.remove_team_user(team_token, email)?)
+    }
+
+    /// Returns the number of license credits left on the account owning the application (see the licensing module).
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn get_license_credits(&self, app_token: &str) -> Result<u32, PushoverError> {
+        self.call(ClientCore::get_license_credits(app_token))
+    }
+
+    /// Assigns a license to a user, optionally restricted to a platform, consuming one credit (see the licensing module).
+    ///
+    /// A request refused by the API (status 0) is returned as an error.
+    pub fn assign_license(&self, app_token: &str, assignee: LicenseAssignee, os: Option<LicenseOs>) -> Result<PushoverResponse, PushoverError> {
+        self.call(self.core.assign_license(app_token, assignee, os)?)
+    }
+
+    /// Sends many push notifications, with at most `concurrency` requests at the same time.
+    ///
+    /// The results are returned in the same order as the messages.
+    pub fn send_all<I>(&self, messages: I) -> Vec<Result<PushoverResponse, PushoverError>>
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        let messages: Vec<Message> = messages.into_iter().collect();
+        let next_index: AtomicUsize = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<PushoverResponse, PushoverError>>>> = Mutex::new(vec![None; messages.len()]);
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.core.concurrency.min(messages.len()) {
+                scope.spawn(|| loop {
+                    let index: usize = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(message) = messages.get(index) else {
+                        break;
+                    };
+                    let result = self.send(message);
+                    results.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .into_iter()
+            .map(|result| result.expect("Every message is sent by a worker"))
+            .collect()
+    }
+
+    /// Sends a same push notification to many users and/or groups.
+    ///
+    /// The message's user key is replaced by the recipients' keys, sent in batches of up to 50 keys.
+    /// Returns the result of the delivery for each recipient key.
+    pub fn send_to_recipients(&self, message: &Message, recipients: &Recipients) -> HashMap<String, Result<PushoverResponse, PushoverError>> {
+        let mut results: HashMap<String, Result<PushoverResponse, PushoverError>> = HashMap::with_capacity(recipients.len());
+
+        for batch in recipients.batches() {
+            let result: Result<PushoverResponse, PushoverError> = self.send(&client_core::batch_message(message, batch));
+
+            if client_core::retry_individually(recipients, batch, &result) {
+                let single_messages = batch.iter().map(|key| client_core::batch_message(message, std::slice::from_ref(key)));
+                let single_results = self.send_all(single_messages);
+                results.extend(batch.iter().cloned().zip(single_results));
+            } else {
+                for key in batch {
+                    results.insert(key.clone(), result.clone());
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Sends a push notification without attachment, see send
+    fn deliver(&self, message: &Message) -> Result<PushoverResponse, PushoverError> {
+        let (call, window_key) = self.core.message(message)?;
+        let response: PushoverResponse = self.call(call)?;
+        self.core.message_sent(window_key);
+        Ok(response)
+    }
+
+    /// Sends a push notification with attachment, see send_with_attachment
+    fn deliver_with_attachment(&self, message: &AttachmentMessage) -> Result<PushoverResponse, PushoverError> {
+        if let Some(response) = self.core.attachment_dry_run(message)? {
+            return Ok(response);
+        }
+
+        let attachment_bytes: Vec<u8> = std::fs::read(&message.attachment)?;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("attachment_bytes", attachment_bytes.len());
+        self.call(ClientCore::attachment(message, attachment_bytes))
+    }
+
+    /// Makes an API call: sends its request (if any) and reads the response
+    fn call<T>(&self, call: Call<T>) -> Result<T, PushoverError> {
+        match call {
+            Call::Done(value) => Ok(value),
+            Call::Send(request, read) => {
+                let response: TransportResponse = self.execute(request)?;
+                read(&response).map_err(|error| error.with_http_status(response.status))
+            },
+        }
+    }
+
+    /// Sends a request through the transport, once the pacing allows it
+    fn execute(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError> {
+        if let Some(slot) = self.core.book_slot() {
+            std::thread::sleep(slot.saturating_duration_since(Instant::now()));
+        }
+
+        #[cfg(feature = "tracing")]
+        let span: tracing::Span = instrumentation::request_span(&request);
+        #[cfg(feature = "metrics")]
+        let url: String = request.url.clone();
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let started: Instant = Instant::now();
+
+        let result: Result<TransportResponse, PushoverError> = {
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+            self.transport.send(request)
+        };
+
+        #[cfg(feature = "tracing")]
+        instrumentation::record_response(&span, &result, started);
+        #[cfg(feature = "metrics")]
+        delivery_metrics::record_request(&url, &result, started);
+
+        result
+    }
+}
@@ -0,0 +1,20 @@
+/*!
+Pushover licensing API (https://pushover.net/api/licensing), blocking.
+
+Calls are authenticated with the application token, like message calls.
+These functions use a default `PushoverClient`, see its methods of the same name to use a configured client.
+ */
+
+pub use crate::pushover::data::{LicenseAssignee, LicenseCreditsResponse, LicenseOs};
+use crate::{PushoverError, PushoverResponse};
+use super::PushoverClient;
+
+/// Returns the number of license credits left on the account owning the application (! blocking)
+pub fn get_license_credits(app_token: &str) -> Result<u32, PushoverError> {
+    PushoverClient::new().get_license_credits(app_token)
+}
+
+/// Assigns a license to a user, optionally restricted to a platform, consuming one credit (! blocking)
+pub fn assign_license(app_token: &str, assignee: LicenseAssignee, os: Option<LicenseOs>) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().assign_license(app_token, assignee, os)
+}
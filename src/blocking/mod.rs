@@ -0,0 +1,39 @@
+/*!
+Blocking counterparts of the crate's async API, built on `reqwest::blocking`. (Requires the `blocking` feature, enabled by default)
+
+These functions must not be called from within an async runtime.
+ */
+
+mod client;
+mod transport;
+pub mod teams;
+pub mod licensing;
+
+pub use client::PushoverClient;
+pub use transport::{ReqwestTransport, Transport};
+
+use std::collections::HashMap;
+
+use crate::{AttachmentMessage, Message, PushoverError, PushoverResponse, Recipients};
+
+/// Send a push notification without attachment (! blocking)
+///
+/// Uses a default `PushoverClient`. A request refused by the API is returned as a response with a status of 0.
+pub fn send_pushover_request(message: Message) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
+    Ok(PushoverClient::new().send(&message).or_else(PushoverResponse::from_refusal)?)
+}
+
+/// Send a push notification with attachment (! blocking)
+///
+/// Uses a default `PushoverClient`. A request refused by the API is returned as a response with a status of 0.
+pub fn send_pushover_request_with_attachment(message: AttachmentMessage) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
+    Ok(PushoverClient::new().send_with_attachment(&message).or_else(PushoverResponse::from_refusal)?)
+}
+
+/// Send a same push notification to many users and/or groups (! blocking)
+///
+/// The message's user key is replaced by the recipients' keys, sent in batches of up to 50 keys.
+/// Returns the result of the delivery for each recipient key.
+pub fn send_pushover_request_to_recipients(message: Message, recipients: &Recipients) -> HashMap<String, Result<PushoverResponse, PushoverError>> {
+    PushoverClient::new().send_to_recipients(&message, recipients)
+}
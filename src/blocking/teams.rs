@@ -0,0 +1,25 @@
+/*!
+Pushover for Teams API (https://pushover.net/api/teams), blocking.
+
+Every call is authenticated with a team token, which is distinct from your application token.
+These functions use a default `PushoverClient`, see its methods of the same name to use a configured client.
+ */
+
+pub use crate::pushover::data::{TeamMember, TeamResponse, TeamUser, TeamUserBuilder};
+use crate::{PushoverError, PushoverResponse};
+use super::PushoverClient;
+
+/// Shows the team's name and members (! blocking)
+pub fn get_team(team_token: &str) -> Result<TeamResponse, PushoverError> {
+    PushoverClient::new().get_team(team_token)
+}
+
+/// Adds a user to the team, creating their account if needed (! blocking)
+pub fn add_team_user(user: TeamUser) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().add_team_user(&user)
+}
+
+/// Removes a user from the team, identified by their e-mail address (! blocking)
+pub fn remove_team_user(team_token: &str, email: &str) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().remove_team_user(team_token, email)
+}
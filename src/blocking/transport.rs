@@ -0,0 +1,49 @@
+use crate::transport::{headers_of, Method, TransportRequest, TransportResponse};
+use crate::PushoverError;
+
+/**
+ Sends encoded requests to the Pushover API and returns the raw responses, blocking until done.
+ **/
+pub trait Transport: Send + Sync {
+    /// Sends the request. HTTP error statuses are not errors, the API describes them in the response body.
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError>;
+}
+
+/**
+ The default blocking transport, built on a blocking reqwest client.
+ **/
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        ReqwestTransport::default()
+    }
+
+    /// Uses an existing reqwest client, eg: configured with a proxy or timeouts
+    pub fn with_client(client: reqwest::blocking::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError> {
+        let method: reqwest::Method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+        };
+        let mut builder: reqwest::blocking::RequestBuilder = self.client.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let response: reqwest::blocking::Response = builder.body(request.body).send()?;
+        let status: u16 = response.status().as_u16();
+        let headers: Vec<(String, String)> = headers_of(response.headers());
+        let body: Vec<u8> = response.bytes()?.to_vec();
+
+        Ok(TransportResponse { status, headers, body })
+    }
+}
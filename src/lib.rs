@@ -1,44 +1,72 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests;
 mod pushover;
+pub mod teams;
+pub mod licensing;
+pub mod transport;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "blocking")]
+pub mod panic_hook;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+#[cfg(feature = "tracing-layer")]
+pub mod tracing_layer;
+#[cfg(feature = "log")]
+pub mod logger;
+#[cfg(feature = "alertmanager")]
+pub mod alertmanager;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+#[cfg(feature = "smtp")]
+pub mod smtp;
 
-use pushover::constants::PUSHOVER_API_ENDPOINT;
 pub use pushover::data::{MessageBuilder, AttachmentMessageBuilder};
 pub use pushover::data::PushoverSound;
+pub use pushover::data::Priority;
+pub use pushover::data::{IntoDuration, IntoTimestamp};
 pub use pushover::data::Message;
 pub use pushover::data::AttachmentMessage;
 pub use pushover::data::PushoverResponse;
+pub use pushover::data::{AppLimitsResponse, ReceiptResponse, UserValidationResponse};
+pub use pushover::data::PushoverError;
+pub use pushover::data::Recipients;
+pub use pushover::data::{PreparedAttachment, PreparedBody, PreparedRequest};
+pub use pushover::data::MessageTemplate;
+pub use pushover::data::{Deduplication, DeduplicationStats};
+#[cfg(feature = "schedule")]
+pub use pushover::data::{OffHours, Schedule, ScheduleDecision};
+pub use pushover::client::PushoverClient;
+
+use std::collections::HashMap;
 
 /// Send a push notification without attachment (non-blocking)
+///
+/// Uses a default `PushoverClient`, which needs a tokio runtime. A request refused by the API is returned as a response with a status of 0.
 pub async fn send_pushover_request(message: Message) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
-    let client: reqwest::Client = reqwest::Client::new();
-    let response: reqwest::Response = client
-        .post(PUSHOVER_API_ENDPOINT)
-        .json(&message)
-        .send()
-        .await?;
-    PushoverResponse::try_from_reqwest_response(response).await
+    Ok(PushoverClient::new().send(&message).await.or_else(PushoverResponse::from_refusal)?)
+}
+
+/// Send a same push notification to many users and/or groups (non-blocking)
+///
+/// The message's user key is replaced by the recipients' keys, sent in batches of up to 50 keys.
+/// Returns the result of the delivery for each recipient key.
+pub async fn send_pushover_request_to_recipients(message: Message, recipients: &Recipients) -> HashMap<String, Result<PushoverResponse, PushoverError>> {
+    PushoverClient::new().send_to_recipients(&message, recipients).await
 }
 
 /// Send a push notification with attachment (! blocking)
+#[cfg(feature = "blocking")]
 pub fn send_pushover_request_with_attachment(message: AttachmentMessage) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
-    let client: reqwest::blocking::Client = reqwest::blocking::Client::new();
-    let form: reqwest::blocking::multipart::Form = message.into_form()?;
-    let response: Result<reqwest::blocking::Response, reqwest::Error> = client
-        .post(PUSHOVER_API_ENDPOINT)
-        .multipart(form)
-        .send();
-    PushoverResponse::try_from_blocking_reqwest_response(response?)
+    blocking::send_pushover_request_with_attachment(message)
 }
 
 /// Send a push notification with attachment asynchronously (non-blocking)
+///
+/// Uses a default `PushoverClient`. A request refused by the API is returned as a response with a status of 0.
 pub async fn send_pushover_request_with_attachment_async(message: AttachmentMessage) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
-    let client: reqwest::Client = reqwest::Client::new();
-    let form: reqwest::multipart::Form = message.into_form_async().await?;
-    let response: Result<reqwest::Response, reqwest::Error> = client
-        .post(PUSHOVER_API_ENDPOINT)
-        .multipart(form)
-        .send()
-        .await;
-    PushoverResponse::try_from_reqwest_response(response?).await
+    Ok(PushoverClient::new().send_with_attachment(&message).await.or_else(PushoverResponse::from_refusal)?)
 }
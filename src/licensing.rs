@@ -0,0 +1,19 @@
+/*!
+Pushover licensing API (https://pushover.net/api/licensing)
+
+Calls are authenticated with the application token, like message calls.
+These functions use a default `PushoverClient`, see its methods of the same name to use a configured client.
+ */
+
+pub use crate::pushover::data::{LicenseAssignee, LicenseCreditsResponse, LicenseOs};
+use crate::{PushoverClient, PushoverError, PushoverResponse};
+
+/// Returns the number of license credits left on the account owning the application (non-blocking)
+pub async fn get_license_credits(app_token: &str) -> Result<u32, PushoverError> {
+    PushoverClient::new().get_license_credits(app_token).await
+}
+
+/// Assigns a license to a user, optionally restricted to a platform, consuming one credit (non-blocking)
+pub async fn assign_license(app_token: &str, assignee: LicenseAssignee, os: Option<LicenseOs>) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().assign_license(app_token, assignee, os).await
+}
@@ -0,0 +1,285 @@
+/*!
+A `log` backend sending the records to Pushover (Requires the `log` feature)
+
+The records are sent by a background thread, which has no async runtime: it uses a `blocking::PushoverClient`,
+which can be given a custom transport (see `set_client`).
+
+```no_run
+use pushover_rs::logger::PushoverLoggerBuilder;
+
+PushoverLoggerBuilder::new("<USER_KEY>", "<APP_TOKEN>")
+    .set_level(log::LevelFilter::Error)
+    .init()
+    .expect("Another logger is already installed");
+```
+ */
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::time::{Duration, Instant};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::blocking::PushoverClient;
+use crate::pushover::constants::{PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::text::truncate;
+use crate::{Message, MessageBuilder, Priority};
+
+/// The name of the thread sending the messages
+const PUSHOVER_LOGGER_THREAD_NAME: &str = "pushover-logger";
+/// By default, at most this many records are waiting to be sent, the next ones are dropped
+const PUSHOVER_LOGGER_DEFAULT_CAPACITY: usize = 256;
+/// By default, at most this many records are sent in a same message
+const PUSHOVER_LOGGER_DEFAULT_BATCH_SIZE: usize = 20;
+/// By default, the records are collected for this long before being sent together
+const PUSHOVER_LOGGER_DEFAULT_BATCH_DELAY: Duration = Duration::from_secs(2);
+/// How long `flush` waits for the pending records to be sent
+const PUSHOVER_LOGGER_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+A `log::Log` implementation sending the records as Pushover messages, from a background thread.
+
+Records are batched: the ones logged within a short delay are sent in a same message, with the highest priority among them.
+Logging never blocks: records exceeding the capacity of the queue are dropped. The crate's own records are never sent.
+ */
+pub struct PushoverLogger {
+    level: LevelFilter,
+    sender: SyncSender<Command>,
+}
+
+/**
+Helps configure a PushoverLogger.
+ */
+#[derive(Debug)]
+pub struct PushoverLoggerBuilder {
+    user_key: String,
+    app_token: String,
+    level: LevelFilter,
+    priorities: HashMap<Level, Priority>,
+    batch_size: usize,
+    batch_delay: Duration,
+    capacity: usize,
+    client: PushoverClient,
+}
+
+enum Command {
+    Log(LogLine),
+    /// Sends the pending records now, then notifies the sender
+    Flush(SyncSender<()>),
+}
+
+/// A formatted record
+struct LogLine {
+    level: Level,
+    target: String,
+    text: String,
+}
+
+/// What the background thread needs to build the messages
+struct Batcher {
+    user_key: String,
+    app_token: String,
+    priorities: HashMap<Level, Priority>,
+    batch_size: usize,
+    batch_delay: Duration,
+    client: PushoverClient,
+}
+
+impl PushoverLoggerBuilder {
+    /// Sends the Warn and Error records to this user.
+    ///
+    /// Errors are sent with a high priority, warnings with a normal one, info with a low one and the rest with the lowest one (silently).
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        Self {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            level: LevelFilter::Warn,
+            priorities: HashMap::from([
+                (Level::Error, Priority::High),
+                (Level::Warn, Priority::Normal),
+                (Level::Info, Priority::Low),
+                (Level::Debug, Priority::Lowest),
+                (Level::Trace, Priority::Lowest),
+            ]),
+            batch_size: PUSHOVER_LOGGER_DEFAULT_BATCH_SIZE,
+            batch_delay: PUSHOVER_LOGGER_DEFAULT_BATCH_DELAY,
+            capacity: PUSHOVER_LOGGER_DEFAULT_CAPACITY,
+            client: PushoverClient::new(),
+        }
+    }
+
+    /// Sends the records of this level and the more severe ones
+    pub fn set_level(mut self, level: LevelFilter) -> PushoverLoggerBuilder {
+        self.level = level;
+        self
+    }
+
+    /// Sets the priority of the records of a level (emergency messages are not supported, they are sent with a high priority)
+    pub fn set_priority(mut self, level: Level, priority: Priority) -> PushoverLoggerBuilder {
+        self.priorities.insert(level, priority.without_emergency());
+        self
+    }
+
+    /// Sends at most `batch_size` records per message, collected for at most `batch_delay`. (Min batch size: 1)
+    pub fn set_batching(mut self, batch_size: usize, batch_delay: Duration) -> PushoverLoggerBuilder {
+        self.batch_size = batch_size.max(1);
+        self.batch_delay = batch_delay;
+        self
+    }
+
+    /// Sets how many records can wait to be sent. (Min: 1)
+    pub fn set_capacity(mut self, capacity: usize) -> PushoverLoggerBuilder {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sends the messages through this client (eg: with a custom transport)
+    pub fn set_client(mut self, client: PushoverClient) -> PushoverLoggerBuilder {
+        self.client = client;
+        self
+    }
+
+    /// Starts the background thread and returns the logger
+    pub fn build(self) -> PushoverLogger {
+        let (sender, receiver) = mpsc::sync_channel::<Command>(self.capacity);
+        let batcher: Batcher = Batcher {
+            user_key: self.user_key,
+            app_token: self.app_token,
+            priorities: self.priorities,
+            batch_size: self.batch_size,
+            batch_delay: self.batch_delay,
+            client: self.client,
+        };
+        std::thread::Builder::new()
+            .name(PUSHOVER_LOGGER_THREAD_NAME.into())
+            .spawn(move || batcher.run(receiver))
+            .expect("Cannot start the Pushover logger thread");
+
+        PushoverLogger { level: self.level, sender }
+    }
+
+    /// Builds the logger and installs it as the global logger
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let logger: PushoverLogger = self.build();
+        let level: LevelFilter = logger.level;
+        log::set_boxed_logger(Box::new(logger))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+impl Log for PushoverLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // The records of the crate, or of the HTTP stack on the sending thread, would loop through this logger
+        metadata.level() <= self.level
+            && !metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && std::thread::current().name() != Some(PUSHOVER_LOGGER_THREAD_NAME)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line: LogLine = LogLine {
+            level: record.level(),
+            target: record.target().to_owned(),
+            text: record.args().to_string(),
+        };
+        // A full queue means Pushover can't keep up, the record is dropped rather than blocking the caller
+        let _ = self.sender.try_send(Command::Log(line));
+    }
+
+    /// Waits (up to 10 seconds) for the pending records to be sent, eg: before exiting
+    fn flush(&self) {
+        let (done_sender, done_receiver) = mpsc::sync_channel::<()>(1);
+        if self.sender.send(Command::Flush(done_sender)).is_ok() {
+            let _ = done_receiver.recv_timeout(PUSHOVER_LOGGER_FLUSH_TIMEOUT);
+        }
+    }
+}
+
+impl std::fmt::Debug for PushoverLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PushoverLogger")
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Batcher {
+    /// Collects the records into batches and sends them, until the logger is dropped
+    fn run(self, receiver: Receiver<Command>) {
+        let mut batch: Vec<LogLine> = Vec::new();
+        let mut deadline: Option<Instant> = None;
+
+        loop {
+            let command: Result<Command, RecvTimeoutError> = match deadline {
+                Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match command {
+                Ok(Command::Log(line)) => {
+                    batch.push(line);
+                    deadline.get_or_insert_with(|| Instant::now() + self.batch_delay);
+                    if batch.len() >= self.batch_size {
+                        self.send(std::mem::take(&mut batch));
+                        deadline = None;
+                    }
+                },
+                Ok(Command::Flush(done)) => {
+                    self.send(std::mem::take(&mut batch));
+                    deadline = None;
+                    let _ = done.send(());
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    self.send(std::mem::take(&mut batch));
+                    deadline = None;
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.send(batch);
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Sends a batch as a single message. Failures can't be reported anywhere but in the logs, so they are ignored.
+    fn send(&self, batch: Vec<LogLine>) {
+        if let Some(message) = self.to_message(&batch) {
+            let _ = self.client.send(&message);
+        }
+    }
+
+    /// Builds the message of a batch: one line per record, the highest priority of the records
+    fn to_message(&self, batch: &[LogLine]) -> Option<Message> {
+        let first: &LogLine = batch.first()?;
+        let title: String = if batch.len() == 1 {
+            first.target.clone()
+        } else if batch.iter().all(|line| line.target == first.target) {
+            format!("{} ({} records)", first.target, batch.len())
+        } else {
+            format!("{} log records", batch.len())
+        };
+
+        let mut text: String = String::new();
+        for line in batch {
+            let _ = writeln!(text, "{:<5} {}: {}", line.level, line.target, line.text);
+        }
+
+        let priority: Priority = batch
+            .iter()
+            .filter_map(|line| self.priorities.get(&line.level))
+            .max_by_key(|priority| priority.level())
+            .cloned()
+            .unwrap_or_default();
+
+        Some(MessageBuilder::new(&self.user_key, &self.app_token, &truncate(text.trim_end(), PUSHOVER_API_MAX_MESSAGE_LENGTH))
+            .set_title(&truncate(&title, PUSHOVER_API_MAX_TITLE_LENGTH))
+            .set_priority(priority)
+            .set_monospace(true)
+            .build())
+    }
+}
@@ -0,0 +1,176 @@
+/*!
+Crash notifications: a panic hook sending the panic as a Pushover message (Requires the `blocking` feature)
+
+```no_run
+use pushover_rs::panic_hook::{install_panic_hook, PanicHookConfig};
+
+install_panic_hook(PanicHookConfig::new("<USER_KEY>", "<APP_TOKEN>"));
+```
+ */
+
+use std::backtrace::Backtrace;
+use std::cell::Cell;
+use std::panic::PanicHookInfo;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::blocking::PushoverClient;
+use crate::pushover::constants::{PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::text::truncate;
+use crate::{Message, MessageBuilder, Priority};
+
+/// By default, this many lines of the backtrace are sent
+const PANIC_HOOK_DEFAULT_BACKTRACE_LINES: usize = 20;
+/// By default, the hook waits this long for the message to be sent
+const PANIC_HOOK_DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The frames of the panic machinery, which the backtrace starts with: the excerpt sent starts after the last of them
+const PANIC_MACHINERY_FRAMES: [&str; 4] = ["rust_begin_unwind", "begin_panic", "__rust_end_short_backtrace", "core::panicking::"];
+
+thread_local! {
+    /// Whether the thread is describing or sending a panic: a panic there is not sent, it would panic again
+    static IN_PANIC_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/**
+The configuration of the panic hook.
+ */
+#[derive(Debug, Clone)]
+pub struct PanicHookConfig {
+    user_key: String,
+    app_token: String,
+    title: Option<String>,
+    priority: Priority,
+    backtrace_lines: usize,
+    timeout: Duration,
+    client: PushoverClient,
+}
+
+impl PanicHookConfig {
+    /// Sends the panics to this user, with a high priority, the first 20 lines of the backtrace, and waiting at most 10 seconds
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        Self {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            title: None,
+            priority: Priority::High,
+            backtrace_lines: PANIC_HOOK_DEFAULT_BACKTRACE_LINES,
+            timeout: PANIC_HOOK_DEFAULT_TIMEOUT,
+            client: PushoverClient::new(),
+        }
+    }
+
+    /// Sets the title of the messages, otherwise "[program name] panicked" is used
+    pub fn set_title(mut self, title: &str) -> PanicHookConfig {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Sets the priority of the messages (emergency messages are not supported, they are sent with a high priority)
+    pub fn set_priority(mut self, priority: Priority) -> PanicHookConfig {
+        self.priority = priority.without_emergency();
+        self
+    }
+
+    /// Sets how many lines of the backtrace are sent (0 to send none)
+    pub fn set_backtrace_lines(mut self, backtrace_lines: usize) -> PanicHookConfig {
+        self.backtrace_lines = backtrace_lines;
+        self
+    }
+
+    /// Sets how long the panicking thread waits for the message to be sent
+    pub fn set_timeout(mut self, timeout: Duration) -> PanicHookConfig {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the messages through this client (eg: with a custom transport)
+    pub fn set_client(mut self, client: PushoverClient) -> PanicHookConfig {
+        self.client = client;
+        self
+    }
+
+    /// Describes a panic from the panic hook
+    fn to_message(&self, info: &PanicHookInfo<'_>) -> Message {
+        let thread = std::thread::current();
+        let location: String = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_else(|| "an unknown location".to_owned());
+        let payload: &str = match info.payload().downcast_ref::<&str>() {
+            Some(payload) => payload,
+            None => info.payload().downcast_ref::<String>().map_or("Box<dyn Any>", |payload| payload.as_str()),
+        };
+
+        self.panic_message(thread.name().unwrap_or("<unnamed>"), &location, payload)
+    }
+
+    /// Describes a panic: the thread, location and message, then the start of the backtrace
+    pub(crate) fn panic_message(&self, thread_name: &str, location: &str, payload: &str) -> Message {
+        let mut text: String = format!("thread '{}' panicked at {}:\n{}", thread_name, location, payload);
+        if self.backtrace_lines > 0 {
+            let backtrace: String = Backtrace::force_capture().to_string();
+            text = format!("{}\n\nBacktrace:\n{}", text, backtrace_excerpt(&backtrace, self.backtrace_lines));
+        }
+
+        let title: String = self.title.clone().unwrap_or_else(|| format!("{} panicked", program_name()));
+
+        MessageBuilder::new(&self.user_key, &self.app_token, &truncate(&text, PUSHOVER_API_MAX_MESSAGE_LENGTH))
+            .set_title(&truncate(&title, PUSHOVER_API_MAX_TITLE_LENGTH))
+            .set_priority(self.priority.clone())
+            .set_monospace(true)
+            .build()
+    }
+}
+
+/// Installs a panic hook sending a message for every panic, after running the previously installed hook.
+///
+/// The panicking thread waits for the message to be sent (or the timeout), so it is delivered before the process exits.
+/// A panic while describing or sending a panic only runs the previous hook.
+pub fn install_panic_hook(config: PanicHookConfig) {
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if IN_PANIC_HOOK.with(Cell::get) {
+            return;
+        }
+
+        IN_PANIC_HOOK.with(|in_hook| in_hook.set(true));
+        let message: Message = config.to_message(info);
+        IN_PANIC_HOOK.with(|in_hook| in_hook.set(false));
+        let client: PushoverClient = config.client.clone();
+        let (sent_sender, sent_receiver) = mpsc::sync_channel::<()>(1);
+        // Blocking requests can't be made from an async runtime's thread, which may be the one panicking
+        let sending = std::thread::Builder::new()
+            .name("pushover-panic-hook".into())
+            .spawn(move || {
+                IN_PANIC_HOOK.with(|in_hook| in_hook.set(true));
+                let _ = client.send(&message);
+                let _ = sent_sender.send(());
+            });
+        if sending.is_ok() {
+            let _ = sent_receiver.recv_timeout(config.timeout);
+        }
+    }));
+}
+
+/// The first lines of a backtrace, from the frame following the panic machinery (or from the start, without it)
+pub(crate) fn backtrace_excerpt(backtrace: &str, lines: usize) -> String {
+    let all: Vec<&str> = backtrace.lines().collect();
+    let is_frame = |line: &str| {
+        line.trim_start().split_once(": ").is_some_and(|(index, _)| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+    };
+
+    let start: usize = all.iter()
+        .rposition(|line| is_frame(line) && PANIC_MACHINERY_FRAMES.iter().any(|frame| line.contains(frame)))
+        .and_then(|last| all.iter().skip(last + 1).position(|line| is_frame(line)).map(|next| last + 1 + next))
+        .unwrap_or(0);
+    all[start..].iter().take(lines).copied().collect::<Vec<&str>>().join("\n")
+}
+
+fn program_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "The program".to_owned())
+}
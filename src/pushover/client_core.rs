@@ -0,0 +1,266 @@
+/*!
+The part of the clients that doesn't depend on how requests are sent: their settings, how requests are built and
+how responses are read.
+
+The async `PushoverClient` and the blocking one both hold a ClientCore, and only differ in how they wait for the transport
+(and read attachments).
+ */
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "schedule")]
+use chrono::Utc;
+
+use crate::pushover::constants::{PUSHOVER_API_APPS_LIMITS_ENDPOINT, PUSHOVER_API_DEFAULT_CONCURRENCY, PUSHOVER_API_ENDPOINT, PUSHOVER_API_LICENSES_ASSIGN_ENDPOINT, PUSHOVER_API_LICENSES_ENDPOINT, PUSHOVER_API_RECEIPTS_ENDPOINT, PUSHOVER_API_TEAMS_ADD_USER_ENDPOINT, PUSHOVER_API_TEAMS_ENDPOINT, PUSHOVER_API_TEAMS_REMOVE_USER_ENDPOINT, PUSHOVER_API_USERS_VALIDATE_ENDPOINT};
+use crate::pushover::data::{AppLimitsResponse, AttachmentMessage, Deduplication, DeduplicationStats, LicenseAssignee, LicenseCreditsResponse, LicenseOs, Message, PreparedBody, PreparedRequest, PushoverError, PushoverResponse, ReceiptResponse, Recipients, TeamResponse, TeamUser, UserValidationResponse};
+#[cfg(feature = "schedule")]
+use crate::pushover::data::{Schedule, ScheduleDecision};
+use crate::pushover::deduplicator::{Deduplicator, WindowKey};
+use crate::pushover::pacer::Pacer;
+use crate::transport::{Method, TransportRequest, TransportResponse};
+
+/// What a client has to do for an API call
+pub(crate) enum Call<T> {
+    /// Nothing to send, this is the result (eg: in dry-run mode)
+    Done(T),
+    /// Send the request, then read its response with the function
+    Send(TransportRequest, fn(&TransportResponse) -> Result<T, PushoverError>),
+}
+
+/// The settings shared by both clients, and the requests of every API call
+#[derive(Clone)]
+pub(crate) struct ClientCore {
+    pub(crate) concurrency: usize,
+    pacer: Option<Arc<Pacer>>,
+    dry_run: Option<Arc<Mutex<Vec<PreparedRequest>>>>,
+    deduplicator: Option<Arc<Deduplicator>>,
+    #[cfg(feature = "schedule")]
+    schedule: Option<Arc<Schedule>>,
+}
+
+impl Default for ClientCore {
+    fn default() -> Self {
+        Self {
+            concurrency: PUSHOVER_API_DEFAULT_CONCURRENCY,
+            pacer: None,
+            dry_run: None,
+            deduplicator: None,
+            #[cfg(feature = "schedule")]
+            schedule: None,
+        }
+    }
+}
+
+impl ClientCore {
+    /// Describes the settings, for the clients' Debug
+    pub(crate) fn debug(&self, name: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(name)
+            .field("concurrency", &self.concurrency)
+            .field("pacer", &self.pacer)
+            .field("dry_run", &self.dry_run.is_some())
+            .field("deduplicator", &self.deduplicator)
+            .finish_non_exhaustive()
+    }
+
+    /* Settings */
+
+    pub(crate) fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency.max(1);
+    }
+
+    pub(crate) fn set_requests_per_second(&mut self, requests_per_second: u32) {
+        self.pacer = (requests_per_second > 0).then(|| Arc::new(Pacer::new(Duration::from_secs(1) / requests_per_second)));
+    }
+
+    pub(crate) fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run.then(|| Arc::new(Mutex::new(Vec::new())));
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    pub(crate) fn take_dry_run_requests(&self) -> Vec<PreparedRequest> {
+        match &self.dry_run {
+            Some(prepared) => std::mem::take(&mut *prepared.lock().unwrap_or_else(|poisoned| poisoned.into_inner())),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_deduplication(&mut self, deduplication: Option<Deduplication>) {
+        self.deduplicator = deduplication.map(|deduplication| Arc::new(Deduplicator::new(deduplication)));
+    }
+
+    pub(crate) fn deduplication_stats(&self) -> DeduplicationStats {
+        self.deduplicator
+            .as_ref()
+            .map(|deduplicator| deduplicator.stats())
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "schedule")]
+    pub(crate) fn set_schedule(&mut self, schedule: Option<Schedule>) {
+        self.schedule = schedule.map(Arc::new);
+    }
+
+    /// Books the time at which the next request may start, if the requests are paced
+    pub(crate) fn book_slot(&self) -> Option<Instant> {
+        self.pacer.as_ref().map(|pacer| pacer.book_slot())
+    }
+
+    /* Calls */
+
+    /// Sends a push notification without attachment, once the schedule and the deduplication allow it.
+    /// Returns the deduplication window to open once it is sent, see message_sent.
+    pub(crate) fn message(&self, message: &Message) -> Result<(Call<PushoverResponse>, Option<WindowKey>), PushoverError> {
+        #[cfg(feature = "schedule")]
+        let scheduled: Option<Message> = self.apply_schedule(message)?;
+        #[cfg(feature = "schedule")]
+        let message: &Message = scheduled.as_ref().unwrap_or(message);
+
+        let (deduplicated, window_key) = match &self.deduplicator {
+            Some(deduplicator) => {
+                let (deduplicated, window_key) = deduplicator.check(message)?;
+                (Some(deduplicated), Some(window_key))
+            },
+            None => (None, None),
+        };
+        let message: &Message = deduplicated.as_ref().unwrap_or(message);
+
+        if self.dry_run.is_some() {
+            return Ok((Call::Done(self.keep_dry_run_request(PreparedRequest::from_message(message)?)), window_key));
+        }
+
+        Ok((Call::Send(TransportRequest::json(PUSHOVER_API_ENDPOINT, message)?, PushoverResponse::try_from_transport_response), window_key))
+    }
+
+    /// Opens the deduplication window of a message that was sent
+    pub(crate) fn message_sent(&self, window_key: Option<WindowKey>) {
+        if let (Some(deduplicator), Some(window_key)) = (&self.deduplicator, window_key) {
+            deduplicator.sent(window_key);
+        }
+    }
+
+    /// Describes a push notification with attachment in dry-run mode, None if it has to be sent (see attachment)
+    pub(crate) fn attachment_dry_run(&self, message: &AttachmentMessage) -> Result<Option<PushoverResponse>, PushoverError> {
+        if self.dry_run.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.keep_dry_run_request(PreparedRequest::from_attachment_message(message)?)))
+    }
+
+    /// Sends a push notification with attachment, given the content of the attached file
+    pub(crate) fn attachment(message: &AttachmentMessage, attachment_bytes: Vec<u8>) -> Call<PushoverResponse> {
+        Call::Send(message.to_transport_request(attachment_bytes), PushoverResponse::try_from_transport_response)
+    }
+
+    pub(crate) fn cancel_receipt(&self, application_token: &str, receipt: &str) -> Result<Call<PushoverResponse>, PushoverError> {
+        let url: String = format!("{}/{}/cancel.json", PUSHOVER_API_RECEIPTS_ENDPOINT, receipt);
+        self.post_json(url, serde_json::json!({ "token": application_token }))
+    }
+
+    pub(crate) fn get_receipt(application_token: &str, receipt: &str) -> Call<ReceiptResponse> {
+        let url: String = format!("{}/{}.json", PUSHOVER_API_RECEIPTS_ENDPOINT, receipt);
+        Call::Send(TransportRequest::get(&url, &[("token", application_token)]), ReceiptResponse::try_from_transport_response)
+    }
+
+    /// Validates a user or group key, and optionally one of their devices. The request only reads data, it is sent even in dry-run mode.
+    pub(crate) fn validate_user(application_token: &str, user_key: &str, device: Option<&str>) -> Result<Call<UserValidationResponse>, PushoverError> {
+        let mut body: serde_json::Value = serde_json::json!({ "token": application_token, "user": user_key });
+        if let Some(device) = device {
+            body["device"] = serde_json::Value::String(device.to_owned());
+        }
+
+        Ok(Call::Send(TransportRequest::json(PUSHOVER_API_USERS_VALIDATE_ENDPOINT, &body)?, UserValidationResponse::try_from_transport_response))
+    }
+
+    pub(crate) fn get_limits(application_token: &str) -> Call<AppLimitsResponse> {
+        Call::Send(TransportRequest::get(PUSHOVER_API_APPS_LIMITS_ENDPOINT, &[("token", application_token)]), AppLimitsResponse::try_from_transport_response)
+    }
+
+    pub(crate) fn get_team(team_token: &str) -> Call<TeamResponse> {
+        Call::Send(TransportRequest::get(PUSHOVER_API_TEAMS_ENDPOINT, &[("token", team_token)]), TeamResponse::try_from_transport_response)
+    }
+
+    pub(crate) fn add_team_user(&self, user: &TeamUser) -> Result<Call<PushoverResponse>, PushoverError> {
+        let body: serde_json::Value = serde_json::to_value(user)
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot serialize the request: {}", error)))?;
+        self.post_json(PUSHOVER_API_TEAMS_ADD_USER_ENDPOINT.to_owned(), body)
+    }
+
+    pub(crate) fn remove_team_user(&self, team_token: &str, email: &str) -> Result<Call<PushoverResponse>, PushoverError> {
+        let body: serde_json::Value = serde_json::json!({ "token": team_token, "email": email });
+        self.post_json(PUSHOVER_API_TEAMS_REMOVE_USER_ENDPOINT.to_owned(), body)
+    }
+
+    pub(crate) fn get_license_credits(app_token: &str) -> Call<u32> {
+        Call::Send(TransportRequest::get(PUSHOVER_API_LICENSES_ENDPOINT, &[("token", app_token)]), |response| {
+            Ok(LicenseCreditsResponse::try_from_transport_response(response)?.credits.unwrap_or(0))
+        })
+    }
+
+    pub(crate) fn assign_license(&self, app_token: &str, assignee: LicenseAssignee, os: Option<LicenseOs>) -> Result<Call<PushoverResponse>, PushoverError> {
+        let body: serde_json::Value = assignee.into_assignment_form(app_token, os)?
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), serde_json::Value::String(value)))
+            .collect();
+        self.post_json(PUSHOVER_API_LICENSES_ASSIGN_ENDPOINT.to_owned(), body)
+    }
+
+    /* Helpers */
+
+    /// Rewrites the message according to the schedule, or returns until when it is deferred
+    #[cfg(feature = "schedule")]
+    fn apply_schedule(&self, message: &Message) -> Result<Option<Message>, PushoverError> {
+        let schedule: &Schedule = match self.schedule.as_deref() {
+            Some(schedule) => schedule,
+            None => return Ok(None),
+        };
+
+        match schedule.apply(message, Utc::now()) {
+            ScheduleDecision::Send(message) => Ok(Some(message)),
+            ScheduleDecision::Defer { until, .. } => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%until, "Deferring the message until business hours");
+                Err(PushoverError::Deferred { until: until.into() })
+            },
+        }
+    }
+
+    /// Posts a JSON body to the API, only describing the request in dry-run mode
+    fn post_json(&self, url: String, body: serde_json::Value) -> Result<Call<PushoverResponse>, PushoverError> {
+        if self.dry_run.is_some() {
+            return Ok(Call::Done(self.keep_dry_run_request(PreparedRequest { method: Method::Post, url, body: PreparedBody::Json(body) })));
+        }
+
+        Ok(Call::Send(TransportRequest::json(&url, &body)?, PushoverResponse::try_from_transport_response))
+    }
+
+    /// Keeps the description of a request that would have been sent
+    fn keep_dry_run_request(&self, prepared: PreparedRequest) -> PushoverResponse {
+        if let Some(kept) = &self.dry_run {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(method = %prepared.method, endpoint = prepared.url, "Dry-run, the request is not sent");
+            kept.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(prepared);
+        }
+
+        PushoverResponse::dry_run()
+    }
+}
+
+/// The message sent to a batch of recipients
+pub(crate) fn batch_message(message: &Message, keys: &[String]) -> Message {
+    let mut batch_message: Message = message.clone();
+    batch_message.user_key = keys.join(",");
+    batch_message
+}
+
+/// Whether a failed batch is sent again once per recipient.
+///
+/// Only a refused user key can be caused by a single recipient, other errors would fail again for each of them.
+pub(crate) fn retry_individually(recipients: &Recipients, batch: &[String], result: &Result<PushoverResponse, PushoverError>) -> bool {
+    matches!(result, Err(PushoverError::InvalidUser { .. })) && recipients.has_individual_fallback() && batch.len() > 1
+}
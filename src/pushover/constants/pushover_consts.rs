@@ -3,3 +3,19 @@
 pub const PUSHOVER_API_ENDPOINT: &str = "https://api.pushover.net/1/messages.json";
 pub const PUSHOVER_API_DOCUMENTATION_URL: &str = "https://pushover.net/api";
 pub const PUSHOVER_API_ATTACHMENT_MAX_SIZE_BYTES: u64 = 2621440;
+pub const PUSHOVER_API_MAX_MESSAGE_LENGTH: usize = 1024;
+pub const PUSHOVER_API_MAX_TITLE_LENGTH: usize = 250;
+pub const PUSHOVER_API_TEAMS_ENDPOINT: &str = "https://api.pushover.net/1/teams.json";
+pub const PUSHOVER_API_TEAMS_ADD_USER_ENDPOINT: &str = "https://api.pushover.net/1/teams/add_user.json";
+pub const PUSHOVER_API_TEAMS_REMOVE_USER_ENDPOINT: &str = "https://api.pushover.net/1/teams/remove_user.json";
+pub const PUSHOVER_API_USERS_VALIDATE_ENDPOINT: &str = "https://api.pushover.net/1/users/validate.json";
+pub const PUSHOVER_API_APPS_LIMITS_ENDPOINT: &str = "https://api.pushover.net/1/apps/limits.json";
+pub const PUSHOVER_API_RECEIPTS_ENDPOINT: &str = "https://api.pushover.net/1/receipts";
+pub const PUSHOVER_API_LICENSES_ENDPOINT: &str = "https://api.pushover.net/1/licenses.json";
+pub const PUSHOVER_API_LICENSES_ASSIGN_ENDPOINT: &str = "https://api.pushover.net/1/licenses/assign.json";
+pub const PUSHOVER_API_MAX_RECIPIENTS_PER_REQUEST: usize = 50;
+pub const PUSHOVER_API_DEFAULT_CONCURRENCY: usize = 2;
+pub const PUSHOVER_API_MIN_RETRY_SECS: u32 = 30;
+pub const PUSHOVER_API_MIN_EXPIRE_SECS: u32 = 60;
+pub const PUSHOVER_API_MAX_EXPIRE_SECS: u32 = 10800;
+pub const PUSHOVER_API_MAX_DEVICE_NAME_LENGTH: usize = 25;
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/**
+The body of an Alertmanager webhook notification (https://prometheus.io/docs/alerting/latest/configuration/#webhook_config)
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertmanagerPayload {
+    /// The version of the payload format ("4")
+    pub version: String,
+    /// The key identifying the group of alerts
+    pub group_key: String,
+    /// How many alerts were left out because of the receiver's max_alerts setting
+    #[serde(default)]
+    pub truncated_alerts: u32,
+    /// The status of the group: firing if at least one alert is firing
+    pub status: AlertStatus,
+    /// The name of the receiver
+    pub receiver: String,
+    #[serde(default)]
+    pub group_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub common_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub common_annotations: BTreeMap<String, String>,
+    /// The URL of the Alertmanager that sent the notification
+    #[serde(default, rename = "externalURL")]
+    pub external_url: String,
+    pub alerts: Vec<Alert>,
+}
+
+/**
+An alert of an Alertmanager notification.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub status: AlertStatus,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    /// When the alert started firing (RFC 3339)
+    #[serde(default)]
+    pub starts_at: String,
+    /// When the alert was resolved (RFC 3339), or "0001-01-01T00:00:00Z" if it is still firing
+    #[serde(default)]
+    pub ends_at: String,
+    /// A link to the source of the alert (eg: a Prometheus graph)
+    #[serde(default, rename = "generatorURL")]
+    pub generator_url: String,
+    /// Identifies the alert across notifications
+    #[serde(default)]
+    pub fingerprint: String,
+}
+
+/**
+The status of an alert.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+impl Alert {
+    /// The alert's fingerprint, or its labels if the Alertmanager didn't send one
+    pub fn key(&self) -> String {
+        if !self.fingerprint.is_empty() {
+            return self.fingerprint.clone();
+        }
+
+        self.labels
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+use crate::transport::TransportResponse;
+use super::PushoverError;
+
+/**
+ Data of the response given by the Pushover API when checking an application's monthly message limit.
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppLimitsResponse {
+    /* Always present in response */
+    /// 1 if the request was valid, 0 otherwise. (Not the HTTP status)
+    pub status: u8,
+    /// The request GUID
+    pub request: String,
+
+    /* Not always present */
+    /// The number of messages the application can send per month
+    pub limit: Option<u32>,
+    /// The number of messages left this month
+    pub remaining: Option<u32>,
+    /// The Unix timestamp at which the count is reset
+    pub reset: Option<u64>,
+    /// Specifies a "app token" error type
+    pub token: Option<String>,
+    /// A list of error messages
+    pub errors: Option<Vec<String>>,
+}
+
+impl AppLimitsResponse {
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<AppLimitsResponse, PushoverError> {
+        let data: AppLimitsResponse = response.json::<AppLimitsResponse>()?;
+
+        if data.status != 1 {
+            return Err(PushoverError::from_api_response(data.request, data.token.as_deref(), None, data.errors));
+        }
+
+        Ok(data)
+    }
+}
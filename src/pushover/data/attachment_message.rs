@@ -1,18 +1,33 @@
 use reqwest::multipart::{Form, Part};
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::io;
+use std::path::Path;
 
-#[derive(Debug, Clone, Serialize)]
+use crate::pushover::constants::PUSHOVER_API_ENDPOINT;
+use crate::pushover::runtime;
+use crate::transport::{MultipartFile, TransportRequest};
+use super::device_list::check_devices;
+use super::validation::*;
+use super::PushoverError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 /**
  A message containing an attachment, to be used in conjunction with the send_pushover_request_with_attachment function.
 
  Note: It is preferred to create a Message through the AttachmentMessageBuilder.
+
+ An attachment message can also be deserialized (eg: from a configuration file), its numeric fields being given
+ either as numbers or strings, and its tokens named "app_token"/"user_key" or "token"/"user".
+ Deserialized messages are validated (the attachment file is only checked when building), see validate.
  **/
 pub struct AttachmentMessage {
     /* Required */
     /// (Required) Your app API token, see https://pushover.net/apps/[your application ID]
+    #[serde(alias = "token")]
     pub app_token: String,
     /// (Required) Your User key, see your dashboard (https://pushover.net/ top-right)
+    #[serde(alias = "user")]
     pub user_key: String,
     /// (Required) Your message
     pub message: String,
@@ -21,80 +36,142 @@ pub struct AttachmentMessage {
 
     /* Optional */
     /// The title of the message, otherwise your app's name will be used
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     /// A supplementary URL to show with your message
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     /// A title for your supplementary URL, otherwise just the URL is shown
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url_title: Option<String>,
     /// Send as -2 to generate no notification/alert, -1 to always send as a quiet notification, 1 to display as high-priority and bypass the user's quiet hours, or 2 to also require confirmation from the user
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub priority: Option<String>,
+    #[serde(default, deserialize_with = "number_or_string", skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i8>,
     /// When the priority is set to 2, sets the amount of seconds between each retries. Must be at least 30 seconds.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub retry: Option<String>, // Required if priority is set to 2
+    #[serde(default, deserialize_with = "number_or_string", skip_serializing_if = "Option::is_none")]
+    pub retry: Option<i32>, // Required if priority is set to 2
     /// When the priority is set to 2, sets the amount of seconds before the notification is expired. The maximum value is 10800 (3 hours).be between 60 and 10800.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expire: Option<String>, // Required if priority is set to 2
+    #[serde(default, deserialize_with = "number_or_string", skip_serializing_if = "Option::is_none")]
+    pub expire: Option<i32>, // Required if priority is set to 2
+    /// When the priority is set to 2, a URL called back once the user acknowledges the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<String>,
+    /// When the priority is set to 2, comma-separated tags, to cancel the retries of the messages by tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
     /// The name of one of the sounds supported by device clients to override the user's default sound choice. (See sound list: https://pushover.net/api#sounds)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sound: Option<String>,
     /// A Unix timestamp of your message's date and time to display to the user, rather than the time your message is received by our API
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timestamp: Option<String>, // Year 2038 proof :p
-    /// A device name to send the push notifications to.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "number_or_string", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>, // Year 2038 proof :p
+    /// Comma-separated device names to send the push notifications to. Devices prefixed by a "-" are excluded instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub device: Option<String>,
     /// A TTL (Time to Live) in seconds, after which the message will be automatically deleted from the recipient's inbox.
     /// Setting *ttl* to None prevents this auto removal. Setting TTL to 0 will raise an error (ttl must be > 0).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ttl: Option<u32>,
 }
 
 impl AttachmentMessage {
-    pub fn into_form(self) -> Result<reqwest::blocking::multipart::Form, std::io::Error> {
-        let mut form: reqwest::blocking::multipart::Form = reqwest::blocking::multipart::Form::new()
-            .text("token", self.app_token.clone())
-            .text("user", self.user_key.clone())
-            .text("message", self.message.clone())
-            .text("title", self.title.clone().unwrap_or(String::from("")))
-            .text("url", self.url.clone().unwrap_or(String::from("")))
-            .text("url_title", self.url_title.clone().unwrap_or(String::from("")))
-            .text("priority", self.priority.unwrap_or(String::from("")))
-            .text("retry", self.retry.clone().unwrap_or(String::from("")))
-            .text("expire", self.expire.clone().unwrap_or(String::from("")))
-            .text("sound", self.sound.clone().unwrap_or(String::from("")))
-            .text("timestamp", self.timestamp.unwrap_or(String::from("")))
-            .text("device", self.device.clone().unwrap_or(String::from("")));
+    /// Checks the message against the API's rules: tokens, message and attachment set, lengths, device names, and ranges of the priority, retry, expire and TTL
+    pub fn validate(&self) -> Result<(), PushoverError> {
+        check_required("application token", &self.app_token)?;
+        check_required("user key", &self.user_key)?;
+        check_message(&self.message)?;
+        check_required("attachment", &self.attachment)?;
+        check_title(self.title.as_deref())?;
+        check_priority(self.priority)?;
+        check_retry(self.retry)?;
+        check_expire(self.expire)?;
+        check_emergency_only(self.priority, self.retry, self.expire, &self.callback, &self.tags)?;
+        check_devices(self.device.as_deref())?;
+        check_ttl(self.ttl)
+    }
+
+    /// The text fields of the multipart form, the attachment excluded
+    pub(crate) fn form_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields: Vec<(&'static str, String)> = vec![
+            ("token", self.app_token.clone()),
+            ("user", self.user_key.clone()),
+            ("message", self.message.clone()),
+            ("title", self.title.clone().unwrap_or_default()),
+            ("url", self.url.clone().unwrap_or_default()),
+            ("url_title", self.url_title.clone().unwrap_or_default()),
+            ("priority", self.priority.map(|priority| priority.to_string()).unwrap_or_default()),
+            ("retry", self.retry.map(|retry| retry.to_string()).unwrap_or_default()),
+            ("expire", self.expire.map(|expire| expire.to_string()).unwrap_or_default()),
+            ("sound", self.sound.clone().unwrap_or_default()),
+            ("timestamp", self.timestamp.map(|timestamp| timestamp.to_string()).unwrap_or_default()),
+            ("device", self.device.clone().unwrap_or_default()),
+        ];
+        if let Some(callback) = &self.callback {
+            fields.push(("callback", callback.clone()));
+        }
+        if let Some(tags) = &self.tags {
+            fields.push(("tags", tags.clone()));
+        }
         // TTL became required if it has a value, 0 doesn't work anymore.
-        if self.ttl.is_some() {
-            form = form.text("ttl", self.ttl.unwrap_or(999).to_string());
+        if let Some(ttl) = self.ttl {
+            fields.push(("ttl", ttl.to_string()));
         }
+        fields
+    }
+
+    /// The name of the attached file, without its directories
+    pub(crate) fn attachment_file_name(&self) -> String {
+        Path::new(&self.attachment)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.attachment.clone())
+    }
+
+    /// The MIME type of the attachment, guessed from its extension
+    pub(crate) fn attachment_content_type(&self) -> &'static str {
+        let extension: String = Path::new(&self.attachment)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "jpg" | "jpeg" => "image/jpeg",
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "bmp" => "image/bmp",
+            _ => "application/octet-stream",
+        }
+    }
+
+    /// Encodes the message and the attachment's content as a multipart request
+    pub(crate) fn to_transport_request(&self, attachment_bytes: Vec<u8>) -> TransportRequest {
+        let file: MultipartFile = MultipartFile {
+            field_name: "attachment".into(),
+            file_name: self.attachment_file_name(),
+            content_type: self.attachment_content_type().into(),
+            bytes: attachment_bytes,
+        };
+        TransportRequest::multipart(PUSHOVER_API_ENDPOINT, &self.form_fields(), Some(file))
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn into_form(self) -> Result<reqwest::blocking::multipart::Form, std::io::Error> {
+        let form: reqwest::blocking::multipart::Form = self.form_fields()
+            .into_iter()
+            .fold(reqwest::blocking::multipart::Form::new(), |form, (name, value)| form.text(name, value));
         let attachment_part = reqwest::blocking::multipart::Part::bytes(std::fs::read(&self.attachment)?)
-            .file_name(self.attachment.clone());
+            .file_name(self.attachment_file_name());
         Ok(form.part("attachment", attachment_part))
     }
 
     pub async fn into_form_async(self) -> Result<Form, io::Error> {
-        let mut form = Form::new()
-            .text("token", self.app_token.clone())
-            .text("user", self.user_key.clone())
-            .text("message", self.message.clone())
-            .text("title", self.title.clone().unwrap_or(String::from("")))
-            .text("url", self.url.clone().unwrap_or(String::from("")))
-            .text("url_title", self.url_title.clone().unwrap_or(String::from("")))
-            .text("priority", self.priority.unwrap_or(String::from("")))
-            .text("sound", self.sound.clone().unwrap_or(String::from("")))
-            .text("timestamp", self.timestamp.unwrap_or(String::from("")))
-            .text("device", self.device.clone().unwrap_or(String::from("")));
-        if self.ttl.is_some() {
-            form = form.text("ttl", self.ttl.unwrap_or(999).to_string());
-        }
-        let file_bytes = tokio::fs::read(&self.attachment).await?;
+        let form: Form = self.form_fields()
+            .into_iter()
+            .fold(Form::new(), |form, (name, value)| form.text(name, value));
+        let file_bytes = runtime::read_file(self.attachment.clone().into()).await?;
         let part = Part::bytes(file_bytes)
-            .file_name(self.attachment.clone());
+            .file_name(self.attachment_file_name());
         Ok(form.part("attachment", part))
     }
 }
@@ -112,6 +189,8 @@ impl Default for AttachmentMessage {
             priority: None,
             retry: None,
             expire: None,
+            callback: None,
+            tags: None,
             sound: None,
             timestamp: None,
             device: None,
@@ -119,3 +198,38 @@ impl Default for AttachmentMessage {
         }
     }
 }
+
+impl Serialize for AttachmentMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AttachmentMessage::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AttachmentMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let message: AttachmentMessage = AttachmentMessage::deserialize(deserializer)?;
+        message.validate().map_err(serde::de::Error::custom)?;
+        Ok(message)
+    }
+}
+
+/// Deserializes a numeric field, given as a number or a string
+fn number_or_string<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr + TryFrom<i64>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Integer(i64),
+        String(String),
+    }
+
+    Option::<NumberOrString>::deserialize(deserializer)?
+        .map(|value| match value {
+            NumberOrString::Integer(number) => T::try_from(number).map_err(|_| serde::de::Error::custom(format!("{} is out of range", number))),
+            NumberOrString::String(text) => text.trim().parse::<T>().map_err(|_| serde::de::Error::custom(format!("{} is not a valid number", text))),
+        })
+        .transpose()
+}
@@ -2,6 +2,11 @@ use std::io::Error;
 use std::io::ErrorKind;
 use crate::pushover::constants;
 
+use serde::Serialize;
+
+use super::PushoverError;
+use super::device_list::{check_devices, join_devices, with_device};
+use super::{expire_seconds, retry_seconds, whole_seconds, IntoDuration, IntoTimestamp, Priority};
 use super::PushoverSound;
 use super::AttachmentMessage;
 
@@ -17,20 +22,38 @@ pub struct AttachmentMessageBuilder {
 impl AttachmentMessageBuilder {
     /// Creates a new MessageBuilder instance with the required minimal informations (User key, App token & Message)
     pub fn new(user_key: &str, application_token: &str, message: &str) -> Self {
-        let mut build = AttachmentMessage::default();
-        
-        build.user_key = user_key.to_owned();
-        build.app_token = application_token.to_owned();
-        build.message = message.to_owned();
+        let build = AttachmentMessage {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            message: message.to_owned(),
+            ..Default::default()
+        };
 
         AttachmentMessageBuilder {
             build,
         }
     }
 
+    /// Creates an AttachmentMessageBuilder from the fields of a message, held by a serializable value (eg: a serde_json::Value
+    /// or a section of a configuration file). The message is validated, see AttachmentMessage::validate, once the retry and expire
+    /// of an emergency message are defaulted as in build.
+    pub fn from_value<V: Serialize + ?Sized>(value: &V) -> Result<AttachmentMessageBuilder, PushoverError> {
+        let value: serde_json::Value = serde_json::to_value(value)
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot read the message: {}", error)))?;
+        // Not validated yet: the retry and expire may be missing
+        let build: AttachmentMessage = AttachmentMessage::deserialize(value)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid message: {}", error)))?;
+
+        let builder: AttachmentMessageBuilder = AttachmentMessageBuilder {
+            build,
+        }.with_emergency_defaults();
+        builder.build.validate().map_err(|error| PushoverError::InvalidInput(format!("Invalid message: {}", error)))?;
+        Ok(builder)
+    }
+
     /// Modifies the existing message.
     pub fn modify_message(mut self, message: &str) -> AttachmentMessageBuilder {
-        if message.trim().len() == 0 {
+        if message.trim().is_empty() {
             return self;
         }
 
@@ -40,7 +63,7 @@ impl AttachmentMessageBuilder {
 
     /// Sets a title to your message
     pub fn set_title(mut self, title: &str) -> AttachmentMessageBuilder {
-        if title.trim().len() == 0 {
+        if title.trim().is_empty() {
             self.build.title = None;
         }
 
@@ -51,7 +74,7 @@ impl AttachmentMessageBuilder {
     /// Adds a title to your message
     #[deprecated(since="0.3.12", note="Please use set_title instead.")]
     pub fn add_title(mut self, title: &str) -> AttachmentMessageBuilder {
-        if title.trim().len() == 0 {
+        if title.trim().is_empty() {
             self.build.title = None;
         }
 
@@ -69,7 +92,7 @@ impl AttachmentMessageBuilder {
     ///
     /// If set, the URL title will be shown, otherwise the URL will be shown.
     pub fn set_url(mut self, url: &str, url_title: Option<&str>) -> AttachmentMessageBuilder {
-        if url.trim().len() == 0 {
+        if url.trim().is_empty() {
             self.build.url = None;
             self.build.url_title = None;
             return self;
@@ -77,8 +100,8 @@ impl AttachmentMessageBuilder {
 
         self.build.url = Some(url.to_owned());
 
-        if url_title.is_some() {
-            self.build.url_title = Some(url_title.unwrap().to_owned());
+        if let Some(url_title) = url_title {
+            self.build.url_title = Some(url_title.to_owned());
         }
 
         self
@@ -89,7 +112,7 @@ impl AttachmentMessageBuilder {
     /// If set, the URL title will be shown, otherwise the URL will be shown.
     #[deprecated(since="0.3.12", note="Please use set_url instead.")]
     pub fn add_url(mut self, url: &str, url_title: Option<&str>) -> AttachmentMessageBuilder {
-        if url.trim().len() == 0 {
+        if url.trim().is_empty() {
             self.build.url = None;
             self.build.url_title = None;
             return self;
@@ -97,8 +120,8 @@ impl AttachmentMessageBuilder {
 
         self.build.url = Some(url.to_owned());
 
-        if url_title.is_some() {
-            self.build.url_title = Some(url_title.unwrap().to_owned());
+        if let Some(url_title) = url_title {
+            self.build.url_title = Some(url_title.to_owned());
         }
 
         self
@@ -111,59 +134,31 @@ impl AttachmentMessageBuilder {
         self
     }
 
-    /// Send as -2 to generate no notification/alert, -1 to always send as a quiet notification, 1 to display as high-priority and bypass the user's quiet hours, or 2 to also require confirmation from the user.
-    pub fn set_priority(mut self, priority: i8) -> AttachmentMessageBuilder {
-        if priority < -2 || priority > 2 {
-            self.build.priority = Some("0".into());
-            return self;
-        }
-
-        self.build.priority = Some(priority.to_string());
-        self
-    }
-
-    /// Resets the priority to default (0, normal)
-    pub fn remove_priority(mut self) -> AttachmentMessageBuilder {
-        self.build.priority = Some("0".into());
-        self.build.retry = None;
-        self.build.expire = None;
-        self
-    }
-
-    /// When the priority is set to 2, sets the amount of seconds between each retries. Must be at least 30 seconds.
-    pub fn set_retry(mut self, retry_secs: i32) -> AttachmentMessageBuilder {
-        if self.build.priority != Some("2".into()) {
-            // Retry only makes sense if priority is 2
-            return self;
+    /// Sets the priority: Lowest to generate no notification/alert, Low to always send as a quiet notification, High to display as high-priority and bypass the user's quiet hours, or Emergency to also require confirmation from the user.
+    ///
+    /// The retry and expire of an emergency priority are brought within the API's limits.
+    pub fn set_priority(mut self, priority: Priority) -> AttachmentMessageBuilder {
+        let priority: Priority = priority.clamped();
+        self.build.priority = Some(priority.level());
+
+        if let Priority::Emergency { retry, expire, callback, tags } = priority {
+            self.build.retry = Some(retry_seconds(retry));
+            self.build.expire = Some(expire_seconds(expire));
+            self.build.callback = callback;
+            self.build.tags = Some(tags.join(",")).filter(|tags| !tags.is_empty());
         }
-
-        if retry_secs < 30 {
-            self.build.retry = Some("30".into());
-            return self;
+        else {
+            self.build.retry = None;
+            self.build.expire = None;
+            self.build.callback = None;
+            self.build.tags = None;
         }
-
-        self.build.retry = Some(retry_secs.to_string());
         self
     }
 
-    /// When the priority is set to 2, sets the amount of seconds before the notification is expired. The maximum value is 10800 (3 hours). Must be between 60 and 10800.
-    pub fn set_expire(mut self, expire_secs: i32) -> AttachmentMessageBuilder {
-        if self.build.priority != Some("2".into()) {
-            // Expire only makes sense if priority is 2
-            return self;
-        }
-
-        if expire_secs < 60 {
-            self.build.expire = Some("60".into());
-            return self;
-        }
-        else if expire_secs > 10800 {
-            self.build.expire = Some("10800".into());
-            return self;
-        }
-
-        self.build.expire = Some(expire_secs.to_string());
-        self
+    /// Resets the priority to default (0, normal)
+    pub fn remove_priority(self) -> AttachmentMessageBuilder {
+        self.set_priority(Priority::Normal)
     }
 
     /// Sets the sound to be used to notify the user.
@@ -180,9 +175,11 @@ impl AttachmentMessageBuilder {
         self
     }
 
-    /// Sets an Unix timestamp of your message's date and time to display to the user, rather than the time your message is received by our API
-    pub fn set_timestamp(mut self, unix_timestamp: u64) -> AttachmentMessageBuilder {
-        self.build.timestamp = Some(unix_timestamp.to_string());
+    /// Sets the date and time of your message to display to the user, rather than the time your message is received by our API.
+    ///
+    /// Times before the Unix epoch are ignored.
+    pub fn set_timestamp<T: IntoTimestamp>(mut self, time: T) -> AttachmentMessageBuilder {
+        self.build.timestamp = time.unix_timestamp();
         self
     }
 
@@ -192,28 +189,53 @@ impl AttachmentMessageBuilder {
         self
     }
 
-    /// Add a device name to send the notification to.
+    /// Sets the device to send the notification to.
     ///
-    /// Overrides the current device if a new device name is set.
+    /// Overrides the current devices if a new device name is set.
     pub fn set_device(mut self, device_name: &str) -> AttachmentMessageBuilder {
-        self.build.device = Some(device_name.to_string());
+        self.build.device = join_devices(device_name.split(','));
+        self
+    }
+
+    /// Adds a device to send the notification to, or to exclude when prefixed by a "-" (eg: "-ipad").
+    ///
+    /// Device names are made of up to 25 letters, digits, "_" or "-".
+    pub fn add_device(mut self, device_name: &str) -> AttachmentMessageBuilder {
+        self.build.device = with_device(self.build.device.take(), device_name);
+        self
+    }
+
+    /// Excludes a device: the notification is sent to all the other devices (unless some are added)
+    pub fn exclude_device(self, device_name: &str) -> AttachmentMessageBuilder {
+        let device_name: &str = device_name.trim().trim_start_matches('-');
+        if device_name.is_empty() {
+            return self;
+        }
+        self.add_device(&format!("-{}", device_name))
+    }
+
+    /// Sets the devices to send the notification to (or to exclude, see add_device)
+    pub fn set_devices<I, S>(mut self, device_names: I) -> AttachmentMessageBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.build.device = join_devices(device_names);
         self
     }
 
-    /// Clears the device if set.
+    /// Clears the devices if set.
     pub fn remove_device(mut self) -> AttachmentMessageBuilder {
         self.build.device = None;
         self
     }
 
-    /// Set the TTL (Time to Live), in seconds
-    pub fn set_ttl(mut self, ttl_secs: u32) -> AttachmentMessageBuilder {
-        if ttl_secs <= 0 {
-            self.build.ttl = None;
-        }
-        else {
-            self.build.ttl = Some(ttl_secs);
-        }
+    /// Set the TTL (Time to Live), after which the message is deleted from the recipient's devices.
+    ///
+    /// A started second counts as a whole one, a zero TTL removes it.
+    pub fn set_ttl<D: IntoDuration>(mut self, ttl: D) -> AttachmentMessageBuilder {
+        let ttl_secs: u64 = whole_seconds(ttl.into_duration());
+        self.build.ttl = Some(ttl_secs.min(u32::MAX.into()) as u32).filter(|ttl_secs| *ttl_secs > 0);
         self
     }
 
@@ -223,7 +245,7 @@ impl AttachmentMessageBuilder {
     /// 
     /// Adding an attachment will make the API call blocking.
     pub fn set_attachment(mut self, attachment_path: String) -> AttachmentMessageBuilder {
-        if attachment_path.trim().len() == 0 {
+        if attachment_path.trim().is_empty() {
             return self;
         }
 
@@ -233,14 +255,7 @@ impl AttachmentMessageBuilder {
 
     /// Transforms the MessageBuilder into a useable Message
     pub fn build(mut self) -> Result<AttachmentMessage, Box<dyn std::error::Error>> {
-        if self.build.priority == Some("2".into()) {
-            if self.build.retry.is_none() {
-                self.build.retry = Some("30".into());
-            }
-            if self.build.expire.is_none() {
-                self.build.expire = Some("10800".into());
-            }
-        }
+        self = self.with_emergency_defaults();
 
         if self.build.app_token.is_empty() {
             return Err(Box::new(Error::new(ErrorKind::InvalidInput, "Application token is empty")));
@@ -258,6 +273,8 @@ impl AttachmentMessageBuilder {
             return Err(Box::new(Error::new(ErrorKind::InvalidInput, "Attachment is empty")));
         }
 
+        check_devices(self.build.device.as_deref())?;
+
         // Check if the attachment file path exists
         if !std::path::Path::new(&self.build.attachment).exists() {
             return Err(Box::new(Error::new(ErrorKind::InvalidInput, "Attachment file doesn't exist.")));
@@ -271,4 +288,30 @@ impl AttachmentMessageBuilder {
 
         Ok(self.build.clone())
     }
-}
\ No newline at end of file
+
+    /// Defaults the retry and expire of an emergency message (30 seconds, 3 hours)
+    fn with_emergency_defaults(mut self) -> AttachmentMessageBuilder {
+        if self.build.priority == Some(2) {
+            if self.build.retry.is_none() {
+                self.build.retry = Some(30);
+            }
+            if self.build.expire.is_none() {
+                self.build.expire = Some(10800);
+            }
+        }
+        self
+    }
+}
+
+impl TryFrom<&AttachmentMessage> for AttachmentMessageBuilder {
+    type Error = PushoverError;
+
+    /// Continues building from an existing message, once validated
+    fn try_from(message: &AttachmentMessage) -> Result<Self, Self::Error> {
+        message.validate()?;
+
+        Ok(AttachmentMessageBuilder {
+            build: message.clone(),
+        })
+    }
+}
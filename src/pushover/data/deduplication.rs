@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::Message;
+
+/// Computes the deduplication key of a message
+type KeyFn = dyn Fn(&Message) -> String + Send + Sync;
+
+/**
+A policy suppressing repeated messages, eg: the alerts of a flapping check.
+
+A message is a duplicate when another message with the same key was sent to the same recipient less than `window` ago.
+By default the key is made of the title and the message, see set_key to provide another one.
+
+When summaries are enabled, the next message sent after a window where duplicates were suppressed
+mentions how many were (eg: "(12 similar alerts suppressed)"), if it is sent within an hour of the window's end.
+ **/
+#[derive(Clone)]
+pub struct Deduplication {
+    /// How long a message suppresses its duplicates
+    pub window: Duration,
+    /// Whether the number of suppressed duplicates is reported
+    pub summary: bool,
+    key: Option<Arc<KeyFn>>,
+}
+
+/**
+The counters of a deduplication policy.
+ **/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeduplicationStats {
+    /// Messages let through
+    pub forwarded: u64,
+    /// Messages suppressed as duplicates
+    pub suppressed: u64,
+    /// Messages reporting suppressed duplicates
+    pub summaries: u64,
+}
+
+impl Deduplication {
+    /// Suppresses the duplicates of a message for the given window, without summary
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            summary: false,
+            key: None,
+        }
+    }
+
+    /// Enables or disables the "N similar alerts suppressed" summaries
+    pub fn set_summary(mut self, summary: bool) -> Deduplication {
+        self.summary = summary;
+        self
+    }
+
+    /// Computes the key of the messages with this function, instead of their title and message
+    pub fn set_key<F>(mut self, key: F) -> Deduplication
+    where
+        F: Fn(&Message) -> String + Send + Sync + 'static,
+    {
+        self.key = Some(Arc::new(key));
+        self
+    }
+
+    /// Goes back to keying the messages on their title and message
+    pub fn remove_key(mut self) -> Deduplication {
+        self.key = None;
+        self
+    }
+
+    /// The key identifying the duplicates of a message
+    pub fn key_of(&self, message: &Message) -> String {
+        match &self.key {
+            Some(key) => key(message),
+            None => format!("{}\n{}", message.title.as_deref().unwrap_or_default(), message.message),
+        }
+    }
+}
+
+impl std::fmt::Debug for Deduplication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Deduplication")
+            .field("window", &self.window)
+            .field("summary", &self.summary)
+            .field("custom_key", &self.key.is_some())
+            .finish()
+    }
+}
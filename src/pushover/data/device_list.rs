@@ -0,0 +1,42 @@
+use crate::pushover::constants::PUSHOVER_API_MAX_DEVICE_NAME_LENGTH;
+use super::PushoverError;
+
+/// Adds a device (or an excluded "-device") to a comma-separated list, once
+pub(crate) fn with_device(devices: Option<String>, device_name: &str) -> Option<String> {
+    join_devices(devices.iter().flat_map(|devices| devices.split(',')).chain([device_name]))
+}
+
+/// Joins device names into a comma-separated list, without blanks nor duplicates. None if there is no device.
+pub(crate) fn join_devices<I, S>(device_names: I) -> Option<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut joined: Vec<String> = Vec::new();
+    for device_name in device_names {
+        let device_name: &str = device_name.as_ref().trim();
+        if !device_name.is_empty() && !joined.iter().any(|joined| joined == device_name) {
+            joined.push(device_name.to_owned());
+        }
+    }
+
+    Some(joined.join(",")).filter(|joined| !joined.is_empty())
+}
+
+/// Checks the names of a comma-separated device list: up to 25 letters, digits, "_" or "-", optionally prefixed by a "-" to exclude the device
+pub(crate) fn check_devices(devices: Option<&str>) -> Result<(), PushoverError> {
+    for device_name in devices.iter().flat_map(|devices| devices.split(',')) {
+        let name: &str = device_name.strip_prefix('-').unwrap_or(device_name);
+        let valid: bool = !name.is_empty()
+            && name.len() <= PUSHOVER_API_MAX_DEVICE_NAME_LENGTH
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+        if !valid {
+            return Err(PushoverError::InvalidInput(format!(
+                "Invalid device name \"{}\": up to {} letters, digits, \"_\" or \"-\" are allowed",
+                device_name, PUSHOVER_API_MAX_DEVICE_NAME_LENGTH,
+            )));
+        }
+    }
+    Ok(())
+}
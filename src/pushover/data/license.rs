@@ -0,0 +1,90 @@
+use serde::Deserialize;
+
+use crate::transport::TransportResponse;
+use super::PushoverError;
+
+/**
+ The recipient of a license, either an existing user or an e-mail address.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseAssignee {
+    /// The user key of an existing Pushover account
+    User(String),
+    /// An e-mail address, an account will be created if none exists yet
+    Email(String),
+}
+
+/**
+ The platform a license is assigned to. When not specified, the license can be used on any platform.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LicenseOs {
+    Android,
+    Ios,
+    Desktop,
+}
+
+impl LicenseAssignee {
+    /// Builds the form of a license assignment request
+    pub(crate) fn into_assignment_form(self, app_token: &str, os: Option<LicenseOs>) -> Result<Vec<(&'static str, String)>, PushoverError> {
+        let mut form: Vec<(&'static str, String)> = vec![("token", app_token.to_owned())];
+
+        match self {
+            LicenseAssignee::User(user_key) if !user_key.trim().is_empty() => form.push(("user", user_key)),
+            LicenseAssignee::Email(email) if !email.trim().is_empty() => form.push(("email", email)),
+            _ => return Err(PushoverError::InvalidInput("License assignee is empty".into())),
+        }
+
+        if let Some(os) = os {
+            form.push(("os", os.to_string()));
+        }
+
+        Ok(form)
+    }
+}
+
+// Enables LicenseOs::[OS].to_string();
+impl std::fmt::Display for LicenseOs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name: &str = match *self {
+            LicenseOs::Android => "Android",
+            LicenseOs::Ios => "iOS",
+            LicenseOs::Desktop => "Desktop",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/**
+ Data of the response given by the Pushover licensing API when checking the remaining credits.
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct LicenseCreditsResponse {
+    /* Always present in response */
+    /// 1 if the request was valid, 0 otherwise. (Not the HTTP status)
+    pub status: u8,
+    /// The request GUID
+    pub request: String,
+
+    /* Not always present */
+    /// The number of license credits left on the account
+    pub credits: Option<u32>,
+    /// Specifies a "app token" error type
+    pub token: Option<String>,
+    /// A list of error messages
+    pub errors: Option<Vec<String>>,
+}
+
+impl LicenseCreditsResponse {
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<LicenseCreditsResponse, PushoverError> {
+        let data: LicenseCreditsResponse = response.json::<LicenseCreditsResponse>()?;
+
+        if data.status != 1 {
+            return Err(PushoverError::from_api_response(data.request, data.token.as_deref(), None, data.errors));
+        }
+
+        Ok(data)
+    }
+}
@@ -1,54 +1,72 @@
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize)]
+use super::device_list::check_devices;
+use super::validation::*;
+use super::PushoverError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 /**
 A message to be used in conjunction with the send_pushover_request function.
 
 Note: It is preferred to create a Message through the MessageBuilder.
+
+A message can also be deserialized (eg: from a configuration file), the tokens being named either
+"token"/"user" (as in the API) or "app_token"/"user_key". Deserialized messages are validated, see validate.
  **/
 pub struct Message {
     /* Required */
     /// (Required) Your app API token, see https://pushover.net/apps/[your application ID]
-    #[serde(rename = "token")]
+    #[serde(rename = "token", alias = "app_token")]
     pub app_token: String,
     /// (Required) Your User key, see your dashboard (https://pushover.net/ top-right)
-    #[serde(rename = "user")]
+    #[serde(rename = "user", alias = "user_key")]
     pub user_key: String,
     /// (Required) Your message
     pub message: String,
 
     /* Optional */
     /// The title of the message, otherwise your app's name will be used
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     /// A supplementary URL to show with your message
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     /// A title for your supplementary URL, otherwise just the URL is shown
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url_title: Option<String>,
     /// Send as -2 to generate no notification/alert, -1 to always send as a quiet notification, 1 to display as high-priority and bypass the user's quiet hours, or 2 to also require confirmation from the user
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub priority: Option<i8>,
     /// When the priority is set to 2, sets the amount of seconds between each retries. Must be at least 30 seconds.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub retry: Option<i32>, // Required if priority is set to 2
     /// When the priority is set to 2, sets the amount of seconds before the notification is expired. The maximum value is 10800 (3 hours).be between 60 and 10800.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expire: Option<i32>, // Required if priority is set to 2
+    /// When the priority is set to 2, a URL called back once the user acknowledges the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<String>,
+    /// When the priority is set to 2, comma-separated tags, to cancel the retries of the messages by tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<String>,
     /// The name of one of the sounds supported by device clients to override the user's default sound choice. (See sound list: https://pushover.net/api#sounds)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sound: Option<String>,
     /// A Unix timestamp of your message's date and time to display to the user, rather than the time your message is received by our API
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<u64>, // Year 2038 proof :p
-    /// A device name to send the push notifications to, if you want to limit the notification to a certain device.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Comma-separated device names to send the push notifications to, if you want to limit the notification to certain devices.
+    /// Devices prefixed by a "-" are excluded instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub device: Option<String>,
     /// A TTL (Time to Live) in seconds, after which the message will be automatically deleted from the recipient's inbox.
     /// Setting *ttl* to None prevents this auto removal. Setting TTL to 0 will raise an error (ttl must be > 0).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ttl: Option<u32>,
+    /// Set to 1 to display the message in a monospace font
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monospace: Option<u8>,
 }
 
 impl Default for Message {
@@ -63,10 +81,48 @@ impl Default for Message {
             priority: None,
             retry: None,
             expire: None,
+            callback: None,
+            tags: None,
             sound: None,
             timestamp: None,
             device: None,
             ttl: None,
+            monospace: None,
+        }
+    }
+}
+
+impl Message {
+    /// Checks the message against the API's rules: tokens and message set, lengths, device names, and ranges of the priority, retry, expire and TTL
+    pub fn validate(&self) -> Result<(), PushoverError> {
+        check_required("application token", &self.app_token)?;
+        check_required("user key", &self.user_key)?;
+        check_message(&self.message)?;
+        check_title(self.title.as_deref())?;
+        check_priority(self.priority)?;
+        check_retry(self.retry)?;
+        check_expire(self.expire)?;
+        check_emergency_only(self.priority, self.retry, self.expire, &self.callback, &self.tags)?;
+        check_devices(self.device.as_deref())?;
+        check_ttl(self.ttl)?;
+
+        if self.monospace.is_some_and(|monospace| monospace > 1) {
+            return Err(PushoverError::InvalidInput("The monospace flag must be 0 or 1".into()));
         }
+        Ok(())
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Message::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let message: Message = Message::deserialize(deserializer)?;
+        message.validate().map_err(serde::de::Error::custom)?;
+        Ok(message)
     }
 }
@@ -1,4 +1,7 @@
-use super::{Message, PushoverSound};
+use serde::Serialize;
+
+use super::device_list::{join_devices, with_device};
+use super::{expire_seconds, retry_seconds, whole_seconds, IntoDuration, IntoTimestamp, Message, Priority, PushoverError, PushoverSound};
 
 // TODO: Fix DRY principle with attachment_message_builder.rs
 
@@ -14,20 +17,38 @@ pub struct MessageBuilder {
 impl MessageBuilder {
     /// Creates a new MessageBuilder instance with the required minimal informations (User key, App token & Message)
     pub fn new(user_key: &str, application_token: &str, message: &str) -> Self {
-        let mut build = Message::default();
-        
-        build.user_key = user_key.to_owned();
-        build.app_token = application_token.to_owned();
-        build.message = message.to_owned();
+        let build = Message {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            message: message.to_owned(),
+            ..Default::default()
+        };
 
         MessageBuilder {
             build,
         }
     }
 
+    /// Creates a MessageBuilder from the fields of a message, held by a serializable value (eg: a serde_json::Value
+    /// or a section of a configuration file). The message is validated, see Message::validate, once the retry and expire
+    /// of an emergency message are defaulted as in build.
+    pub fn from_value<V: Serialize + ?Sized>(value: &V) -> Result<MessageBuilder, PushoverError> {
+        let value: serde_json::Value = serde_json::to_value(value)
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot read the message: {}", error)))?;
+        // Not validated yet: the retry and expire may be missing
+        let build: Message = Message::deserialize(value)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid message: {}", error)))?;
+
+        let builder: MessageBuilder = MessageBuilder {
+            build,
+        }.with_emergency_defaults();
+        builder.build.validate().map_err(|error| PushoverError::InvalidInput(format!("Invalid message: {}", error)))?;
+        Ok(builder)
+    }
+
     /// Modifies the existing message.
     pub fn modify_message(mut self, message: &str) -> MessageBuilder {
-        if message.trim().len() == 0 {
+        if message.trim().is_empty() {
             return self;
         }
 
@@ -37,7 +58,7 @@ impl MessageBuilder {
 
     /// Sets a title to your message
     pub fn set_title(mut self, title: &str) -> MessageBuilder {
-        if title.trim().len() == 0 {
+        if title.trim().is_empty() {
             self.build.title = None;
         }
 
@@ -48,7 +69,7 @@ impl MessageBuilder {
     /// Adds a title to your message
     #[deprecated(since="0.3.12", note="Please use set_title instead.")]
     pub fn add_title(mut self, title: &str) -> MessageBuilder {
-        if title.trim().len() == 0 {
+        if title.trim().is_empty() {
             self.build.title = None;
         }
 
@@ -66,15 +87,15 @@ impl MessageBuilder {
     ///
     /// If set, the URL title will be shown, otherwise the URL will be shown.
     pub fn set_url(mut self, url: &str, url_title: Option<&str>) -> MessageBuilder {
-        if url.trim().len() == 0 {
+        if url.trim().is_empty() {
             self.build.url = None;
             self.build.url_title = None;
             return self;
         }
 
         self.build.url = Some(url.to_owned());
-        if url_title.is_some() {
-            self.build.url_title = Some(url_title.unwrap().to_owned());
+        if let Some(url_title) = url_title {
+            self.build.url_title = Some(url_title.to_owned());
         }
         self
     }
@@ -84,15 +105,15 @@ impl MessageBuilder {
     /// If set, the URL title will be shown, otherwise the URL will be shown.
     #[deprecated(since="0.3.12", note="Please use set_url instead.")]
     pub fn add_url(mut self, url: &str, url_title: Option<&str>) -> MessageBuilder {
-        if url.trim().len() == 0 {
+        if url.trim().is_empty() {
             self.build.url = None;
             self.build.url_title = None;
             return self;
         }
 
         self.build.url = Some(url.to_owned());
-        if url_title.is_some() {
-            self.build.url_title = Some(url_title.unwrap().to_owned());
+        if let Some(url_title) = url_title {
+            self.build.url_title = Some(url_title.to_owned());
         }
         self
     }
@@ -104,59 +125,31 @@ impl MessageBuilder {
         self
     }
 
-    /// Send as -2 to generate no notification/alert, -1 to always send as a quiet notification, 1 to display as high-priority and bypass the user's quiet hours, or 2 to also require confirmation from the user.
-    pub fn set_priority(mut self, priority: i8) -> MessageBuilder {
-        if priority < -2 || priority > 2 {
-            self.build.priority = Some(0);
-            return self;
-        }
-
-        self.build.priority = Some(priority);
-        self
-    }
-
-    /// Resets the priority to default (0, normal)
-    pub fn remove_priority(mut self) -> MessageBuilder {
-        self.build.priority = Some(0);
-        self.build.retry = None;
-        self.build.expire = None;
-        self
-    }
-
-    /// When the priority is set to 2, sets the amount of seconds between each retries. Must be at least 30 seconds.
-    pub fn set_retry(mut self, retry_secs: i32) -> MessageBuilder {
-        if self.build.priority != Some(2) {
-            // Retry only makes sense if priority is 2
-            return self;
+    /// Sets the priority: Lowest to generate no notification/alert, Low to always send as a quiet notification, High to display as high-priority and bypass the user's quiet hours, or Emergency to also require confirmation from the user.
+    ///
+    /// The retry and expire of an emergency priority are brought within the API's limits.
+    pub fn set_priority(mut self, priority: Priority) -> MessageBuilder {
+        let priority: Priority = priority.clamped();
+        self.build.priority = Some(priority.level());
+
+        if let Priority::Emergency { retry, expire, callback, tags } = priority {
+            self.build.retry = Some(retry_seconds(retry));
+            self.build.expire = Some(expire_seconds(expire));
+            self.build.callback = callback;
+            self.build.tags = Some(tags.join(",")).filter(|tags| !tags.is_empty());
         }
-
-        if retry_secs < 30 {
-            self.build.retry = Some(30);
-            return self;
+        else {
+            self.build.retry = None;
+            self.build.expire = None;
+            self.build.callback = None;
+            self.build.tags = None;
         }
-
-        self.build.retry = Some(retry_secs);
         self
     }
 
-    /// When the priority is set to 2, sets the amount of seconds before the notification is expired. The maximum value is 10800 (3 hours). Must be between 60 and 10800.
-    pub fn set_expire(mut self, expire_secs: i32) -> MessageBuilder {
-        if self.build.priority != Some(2) {
-            // Expire only makes sense if priority is 2
-            return self;
-        }
-
-        if expire_secs < 60 {
-            self.build.expire = Some(60);
-            return self;
-        }
-        else if expire_secs > 10800 {
-            self.build.expire = Some(10800);
-            return self;
-        }
-
-        self.build.expire = Some(expire_secs);
-        self
+    /// Resets the priority to default (0, normal)
+    pub fn remove_priority(self) -> MessageBuilder {
+        self.set_priority(Priority::Normal)
     }
 
     /// Sets the sound to be used to notify the user.
@@ -173,9 +166,11 @@ impl MessageBuilder {
         self
     }
 
-    /// Sets an Unix timestamp of your message's date and time to display to the user, rather than the time your message is received by our API
-    pub fn set_timestamp(mut self, unix_timestamp: u64) -> MessageBuilder {
-        self.build.timestamp = Some(unix_timestamp);
+    /// Sets the date and time of your message to display to the user, rather than the time your message is received by our API.
+    ///
+    /// Times before the Unix epoch are ignored.
+    pub fn set_timestamp<T: IntoTimestamp>(mut self, time: T) -> MessageBuilder {
+        self.build.timestamp = time.unix_timestamp();
         self
     }
 
@@ -185,33 +180,69 @@ impl MessageBuilder {
         self
     }
 
-    /// Add a device name to send the notification to.
+    /// Sets the device to send the notification to.
     ///
-    /// Overrides the current device if a new device name is set.
+    /// Overrides the current devices if a new device name is set.
     pub fn set_device(mut self, device_name: &str) -> MessageBuilder {
-        self.build.device = Some(device_name.to_string());
+        self.build.device = join_devices(device_name.split(','));
+        self
+    }
+
+    /// Adds a device to send the notification to, or to exclude when prefixed by a "-" (eg: "-ipad").
+    ///
+    /// Device names are made of up to 25 letters, digits, "_" or "-".
+    pub fn add_device(mut self, device_name: &str) -> MessageBuilder {
+        self.build.device = with_device(self.build.device.take(), device_name);
         self
     }
 
-    /// Clears the device if set.
+    /// Excludes a device: the notification is sent to all the other devices (unless some are added)
+    pub fn exclude_device(self, device_name: &str) -> MessageBuilder {
+        let device_name: &str = device_name.trim().trim_start_matches('-');
+        if device_name.is_empty() {
+            return self;
+        }
+        self.add_device(&format!("-{}", device_name))
+    }
+
+    /// Sets the devices to send the notification to (or to exclude, see add_device)
+    pub fn set_devices<I, S>(mut self, device_names: I) -> MessageBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.build.device = join_devices(device_names);
+        self
+    }
+
+    /// Clears the devices if set.
     pub fn remove_device(mut self) -> MessageBuilder {
         self.build.device = None;
         self
     }
 
-    /// Set the TTL (Time to Live), in seconds
-    pub fn set_ttl(mut self, ttl_secs: u32) -> MessageBuilder {
-        if ttl_secs <= 0 {
-            self.build.ttl = None;
-        }
-        else {
-            self.build.ttl = Some(ttl_secs);
-        }
+    /// Set the TTL (Time to Live), after which the message is deleted from the recipient's devices.
+    ///
+    /// A started second counts as a whole one, a zero TTL removes it.
+    pub fn set_ttl<D: IntoDuration>(mut self, ttl: D) -> MessageBuilder {
+        let ttl_secs: u64 = whole_seconds(ttl.into_duration());
+        self.build.ttl = Some(ttl_secs.min(u32::MAX.into()) as u32).filter(|ttl_secs| *ttl_secs > 0);
+        self
+    }
+
+    /// Displays the message in a monospace font (eg: for tables or logs)
+    pub fn set_monospace(mut self, monospace: bool) -> MessageBuilder {
+        self.build.monospace = monospace.then_some(1);
         self
     }
 
     /// Transforms the MessageBuilder into a usable Message
-    pub fn build(mut self) -> Message {
+    pub fn build(self) -> Message {
+        self.with_emergency_defaults().build
+    }
+
+    /// Defaults the retry and expire of an emergency message (30 seconds, 3 hours)
+    fn with_emergency_defaults(mut self) -> MessageBuilder {
         if self.build.priority == Some(2) {
             if self.build.retry.is_none() {
                 self.build.retry = Some(30);
@@ -220,6 +251,26 @@ impl MessageBuilder {
                 self.build.expire = Some(10800);
             }
         }
-        self.build
+        self
+    }
+
+    /// Transforms the MessageBuilder into a usable Message, once validated (see Message::validate)
+    pub fn try_build(self) -> Result<Message, PushoverError> {
+        let message: Message = self.build();
+        message.validate()?;
+        Ok(message)
+    }
+}
+
+impl TryFrom<&Message> for MessageBuilder {
+    type Error = PushoverError;
+
+    /// Continues building from an existing message, once validated
+    fn try_from(message: &Message) -> Result<Self, Self::Error> {
+        message.validate()?;
+
+        Ok(MessageBuilder {
+            build: message.clone(),
+        })
     }
 }
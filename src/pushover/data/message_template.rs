@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{MessageBuilder, Priority, PushoverError, PushoverSound};
+
+/**
+A reusable message shape, with `{placeholder}` variables in its texts.
+
+Variables are looked up in the value the template is rendered against (a map or any Serialize struct),
+nested fields can be reached with dots (eg: `{host.name}`). Use `{{` and `}}` for literal braces.
+
+A template can be loaded from a TOML (`toml` feature) or YAML (`yaml` feature) file, with the same field names:
+```toml
+title = "{host} is down"
+message = "Check {check} failed: {output}"
+url = "https://monitoring.example.com/hosts/{host}"
+priority = 1
+sound = "siren"
+```
+ **/
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    /// (Required) The message
+    pub message: String,
+    /// The title of the message, otherwise your app's name will be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A supplementary URL to show with your message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// A title for your supplementary URL, otherwise just the URL is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_title: Option<String>,
+    /// The priority, written from -2 to 2 in files (not templated)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// The name of one of the sounds supported by Pushover (See sound list: https://pushover.net/api#sounds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+}
+
+impl MessageTemplate {
+    /// Creates a new template with the required message
+    pub fn new(message: &str) -> Self {
+        MessageTemplate {
+            message: message.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the title template
+    pub fn set_title(mut self, title: &str) -> MessageTemplate {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Sets the url template (and optionally, the url title template)
+    pub fn set_url(mut self, url: &str, url_title: Option<&str>) -> MessageTemplate {
+        self.url = Some(url.to_owned());
+        self.url_title = url_title.map(|url_title| url_title.to_owned());
+        self
+    }
+
+    /// Sets the priority of the rendered messages
+    pub fn set_priority(mut self, priority: Priority) -> MessageTemplate {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Sets the sound of the rendered messages
+    pub fn set_sound(mut self, sound: PushoverSound) -> MessageTemplate {
+        self.sound = Some(sound.to_string());
+        self
+    }
+
+    /// Renders the template against the variables, into a MessageBuilder that can be further customized.
+    ///
+    /// Fails if a variable is missing or the template is malformed.
+    pub fn render<V: Serialize + ?Sized>(&self, user_key: &str, application_token: &str, variables: &V) -> Result<MessageBuilder, PushoverError> {
+        let variables: Value = serde_json::to_value(variables)
+            .map_err(|error| PushoverError::Template(format!("Cannot read the template variables: {}", error)))?;
+
+        let message: String = interpolate(&self.message, &variables)?;
+        let mut builder: MessageBuilder = MessageBuilder::new(user_key, application_token, &message);
+
+        if let Some(title) = &self.title {
+            builder = builder.set_title(&interpolate(title, &variables)?);
+        }
+
+        if let Some(url) = &self.url {
+            let url_title: Option<String> = self.url_title
+                .as_ref()
+                .map(|url_title| interpolate(url_title, &variables))
+                .transpose()?;
+            builder = builder.set_url(&interpolate(url, &variables)?, url_title.as_deref());
+        }
+
+        if let Some(priority) = &self.priority {
+            builder = builder.set_priority(priority.clone());
+        }
+
+        if let Some(sound) = &self.sound {
+            let sound: PushoverSound = sound.parse()
+                .map_err(|_| PushoverError::Template(format!("Unknown sound: {}", sound)))?;
+            builder = builder.set_sound(sound);
+        }
+
+        Ok(builder)
+    }
+
+    /// Reads a template from TOML
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<MessageTemplate, PushoverError> {
+        toml::from_str(toml).map_err(|error| PushoverError::Template(format!("Invalid TOML template: {}", error)))
+    }
+
+    /// Reads a template from a TOML file
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file<P: AsRef<std::path::Path>>(path: P) -> Result<MessageTemplate, PushoverError> {
+        MessageTemplate::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Reads a template from YAML
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(yaml: &str) -> Result<MessageTemplate, PushoverError> {
+        serde_yaml_ng::from_str(yaml).map_err(|error| PushoverError::Template(format!("Invalid YAML template: {}", error)))
+    }
+
+    /// Reads a template from a YAML file
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_file<P: AsRef<std::path::Path>>(path: P) -> Result<MessageTemplate, PushoverError> {
+        MessageTemplate::from_yaml_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Replaces the `{placeholder}`s of a text by the variables' values
+fn interpolate(template: &str, variables: &Value) -> Result<String, PushoverError> {
+    let mut rendered: String = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                rendered.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                rendered.push('}');
+            },
+            '{' => {
+                let mut name: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(PushoverError::Template(format!("Unclosed placeholder in \"{}\"", template))),
+                    }
+                }
+                rendered.push_str(&lookup(variables, name.trim())?);
+            },
+            '}' => return Err(PushoverError::Template(format!("Unopened placeholder in \"{}\"", template))),
+            c => rendered.push(c),
+        }
+    }
+
+    Ok(rendered)
+}
+
+/// Finds a (dotted) variable and formats its value
+fn lookup(variables: &Value, name: &str) -> Result<String, PushoverError> {
+    let value: &Value = name
+        .split('.')
+        .try_fold(variables, |value, key| match value {
+            Value::Object(object) => object.get(key),
+            Value::Array(array) => key.parse::<usize>().ok().and_then(|index| array.get(index)),
+            _ => None,
+        })
+        .filter(|value| !value.is_null())
+        .ok_or_else(|| PushoverError::Template(format!("Missing template variable: {}", name)))?;
+
+    Ok(match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
@@ -4,11 +4,59 @@ mod message_builder;
 mod attachment_message;
 mod attachment_message_builder;
 mod pushover_sounds;
+mod priority;
+mod time_conversion;
 mod pushover_response;
+mod pushover_error;
+mod team_member;
+mod team_response;
+mod team_user;
+mod team_user_builder;
+mod license;
+mod user_validation;
+mod app_limits;
+mod receipt_response;
+mod recipients;
+mod prepared_request;
+mod validation;
+mod device_list;
+mod message_template;
+mod deduplication;
+#[cfg(feature = "schedule")]
+mod schedule;
+#[cfg(feature = "scheduler")]
+mod scheduled_job;
+#[cfg(feature = "alertmanager")]
+mod alertmanager;
+#[cfg(feature = "webhook")]
+mod webhook_route;
 
 pub use message::*;
 pub use attachment_message::*;
 pub use message_builder::*;
 pub use pushover_sounds::*;
+pub use priority::*;
+pub use time_conversion::*;
 pub use pushover_response::*;
+pub use pushover_error::*;
 pub use attachment_message_builder::*;
+pub use team_member::*;
+pub use team_response::*;
+pub use team_user::*;
+pub use team_user_builder::*;
+pub use license::*;
+pub use user_validation::*;
+pub use app_limits::*;
+pub use receipt_response::*;
+pub use recipients::*;
+pub use prepared_request::*;
+pub use message_template::*;
+pub use deduplication::*;
+#[cfg(feature = "schedule")]
+pub use schedule::*;
+#[cfg(feature = "scheduler")]
+pub use scheduled_job::*;
+#[cfg(feature = "alertmanager")]
+pub use alertmanager::*;
+#[cfg(feature = "webhook")]
+pub use webhook_route::*;
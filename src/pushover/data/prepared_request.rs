@@ -0,0 +1,102 @@
+use serde_json::Value;
+
+use crate::pushover::constants::PUSHOVER_API_ENDPOINT;
+use crate::transport::Method;
+use super::{AttachmentMessage, Message, PushoverError};
+
+/**
+ A description of a request as it would be sent to the Pushover API, produced by a client in dry-run mode.
+
+ Its Display output is stable, so it can be used to snapshot-test notification payloads.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: String,
+    pub body: PreparedBody,
+}
+
+/**
+ The body of a prepared request.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreparedBody {
+    /// A JSON body, used for messages without attachment
+    Json(Value),
+    /// A multipart form, used for messages with attachment
+    Multipart {
+        fields: Vec<(String, String)>,
+        attachment: Option<PreparedAttachment>,
+    },
+}
+
+/**
+ The file that would be attached to a multipart request.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedAttachment {
+    pub path: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+}
+
+impl PreparedRequest {
+    /// Describes the request sending a message without attachment
+    pub fn from_message(message: &Message) -> Result<PreparedRequest, PushoverError> {
+        let body: Value = serde_json::to_value(message)
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot serialize the request: {}", error)))?;
+
+        Ok(PreparedRequest {
+            method: Method::Post,
+            url: PUSHOVER_API_ENDPOINT.to_owned(),
+            body: PreparedBody::Json(body),
+        })
+    }
+
+    /// Describes the request sending a message with attachment. The attachment is not read, only its size is checked.
+    pub fn from_attachment_message(message: &AttachmentMessage) -> Result<PreparedRequest, PushoverError> {
+        let attachment: PreparedAttachment = PreparedAttachment {
+            path: message.attachment.clone(),
+            file_name: message.attachment_file_name(),
+            content_type: message.attachment_content_type().to_owned(),
+            size_bytes: std::fs::metadata(&message.attachment)?.len(),
+        };
+        let fields: Vec<(String, String)> = message.form_fields()
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect();
+
+        Ok(PreparedRequest {
+            method: Method::Post,
+            url: PUSHOVER_API_ENDPOINT.to_owned(),
+            body: PreparedBody::Multipart { fields, attachment: Some(attachment) },
+        })
+    }
+}
+
+impl std::fmt::Display for PreparedRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} {}", self.method, self.url)?;
+
+        match &self.body {
+            PreparedBody::Json(body) => {
+                let pretty: String = serde_json::to_string_pretty(body).map_err(|_| std::fmt::Error)?;
+                writeln!(f, "Content-Type: application/json")?;
+                writeln!(f)?;
+                writeln!(f, "{}", pretty)
+            },
+            PreparedBody::Multipart { fields, attachment } => {
+                writeln!(f, "Content-Type: multipart/form-data")?;
+                writeln!(f)?;
+                for (name, value) in fields {
+                    writeln!(f, "{}: {}", name, value)?;
+                }
+                if let Some(attachment) = attachment {
+                    writeln!(f, "attachment: {} ({}, {} bytes)", attachment.file_name, attachment.content_type, attachment.size_bytes)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
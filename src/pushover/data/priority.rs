@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pushover::constants::{PUSHOVER_API_MAX_EXPIRE_SECS, PUSHOVER_API_MIN_EXPIRE_SECS, PUSHOVER_API_MIN_RETRY_SECS};
+use super::{whole_seconds, IntoDuration, PushoverError};
+
+/**
+ The priority of a message (See: https://pushover.net/api#priority)
+
+ The parameters of emergency messages are part of the Emergency variant, so they can't be set on other priorities.
+
+ In configurations, a priority is written as its level (-2 to 2), emergencies having the default parameters.
+ **/
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "i8", into = "i8")]
+pub enum Priority {
+    /// (-2) No notification nor alert
+    Lowest,
+    /// (-1) Always a quiet notification
+    Low,
+    /// (0) Notified as set by the user
+    #[default]
+    Normal,
+    /// (1) Bypasses the user's quiet hours
+    High,
+    /// (2) Repeated until the user acknowledges it, see Priority::emergency
+    Emergency {
+        /// The delay between each retry. (Min: 30 seconds)
+        retry: Duration,
+        /// The delay after which the retries stop. (Min: 60 seconds, Max: 3 hours)
+        expire: Duration,
+        /// A URL called back once the user acknowledges the message
+        callback: Option<String>,
+        /// Tags, to cancel the retries of every message having one of them at once
+        tags: Vec<String>,
+    },
+}
+
+impl Priority {
+    /// An emergency priority, retried every 30 seconds for 3 hours
+    pub fn emergency() -> Priority {
+        Priority::Emergency {
+            retry: Duration::from_secs(PUSHOVER_API_MIN_RETRY_SECS.into()),
+            expire: Duration::from_secs(PUSHOVER_API_MAX_EXPIRE_SECS.into()),
+            callback: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Sets the delay between each retry of an emergency priority. (Min: 30 seconds)
+    pub fn set_retry<D: IntoDuration>(mut self, delay: D) -> Priority {
+        if let Priority::Emergency { retry, .. } = &mut self {
+            *retry = delay.into_duration();
+        }
+        self.clamped()
+    }
+
+    /// Sets the delay after which the retries of an emergency priority stop. (Min: 60 seconds, Max: 3 hours)
+    pub fn set_expire<D: IntoDuration>(mut self, delay: D) -> Priority {
+        if let Priority::Emergency { expire, .. } = &mut self {
+            *expire = delay.into_duration();
+        }
+        self.clamped()
+    }
+
+    /// Sets the URL called back once the user acknowledges an emergency message
+    pub fn set_callback(mut self, callback_url: &str) -> Priority {
+        if let Priority::Emergency { callback, .. } = &mut self {
+            *callback = Some(callback_url.to_owned()).filter(|callback| !callback.trim().is_empty());
+        }
+        self
+    }
+
+    /// Adds a tag to an emergency priority
+    pub fn add_tag(mut self, tag: &str) -> Priority {
+        if let Priority::Emergency { tags, .. } = &mut self {
+            if !tag.trim().is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_owned());
+            }
+        }
+        self
+    }
+
+    /// The level of the priority, as sent to the API (-2 to 2)
+    pub fn level(&self) -> i8 {
+        match self {
+            Priority::Lowest => -2,
+            Priority::Low => -1,
+            Priority::Normal => 0,
+            Priority::High => 1,
+            Priority::Emergency { .. } => 2,
+        }
+    }
+
+    /// The priority, emergencies being lowered to High, for the senders that can't track their receipts
+    #[cfg(any(feature = "log", feature = "blocking", feature = "tracing-layer"))]
+    pub(crate) fn without_emergency(self) -> Priority {
+        match self {
+            Priority::Emergency { .. } => Priority::High,
+            other => other,
+        }
+    }
+
+    /// Brings the retry and expire of an emergency priority within the API's limits, in whole seconds
+    pub(crate) fn clamped(self) -> Priority {
+        match self {
+            Priority::Emergency { retry, expire, callback, tags } => Priority::Emergency {
+                retry: Duration::from_secs(retry_seconds(retry) as u64),
+                expire: Duration::from_secs(expire_seconds(expire) as u64),
+                callback,
+                tags,
+            },
+            other => other,
+        }
+    }
+}
+
+impl TryFrom<i8> for Priority {
+    type Error = PushoverError;
+
+    /// Reads a priority level (-2 to 2), emergencies having the default parameters
+    fn try_from(level: i8) -> Result<Self, Self::Error> {
+        match level {
+            -2 => Ok(Priority::Lowest),
+            -1 => Ok(Priority::Low),
+            0 => Ok(Priority::Normal),
+            1 => Ok(Priority::High),
+            2 => Ok(Priority::emergency()),
+            other => Err(PushoverError::InvalidInput(format!("The priority must be between -2 and 2, not {}", other))),
+        }
+    }
+}
+
+impl From<Priority> for i8 {
+    fn from(priority: Priority) -> Self {
+        priority.level()
+    }
+}
+
+/// The seconds of a retry delay, within the API's limits
+pub(crate) fn retry_seconds(retry: Duration) -> i32 {
+    whole_seconds(retry).clamp(PUSHOVER_API_MIN_RETRY_SECS.into(), i32::MAX as u64) as i32
+}
+
+/// The seconds of an expire delay, within the API's limits
+pub(crate) fn expire_seconds(expire: Duration) -> i32 {
+    whole_seconds(expire).clamp(PUSHOVER_API_MIN_EXPIRE_SECS.into(), PUSHOVER_API_MAX_EXPIRE_SECS.into()) as i32
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use super::PushoverResponse;
+
+/**
+ An error occurring while talking to the Pushover API.
+ **/
+#[derive(Debug, Clone)]
+pub enum PushoverError {
+    /// The HTTP request could not be completed, or its response could not be read
+    Http(Arc<reqwest::Error>),
+    /// A custom transport could not complete the request
+    Transport(Arc<dyn std::error::Error + Send + Sync>),
+    /// The API's response could not be understood
+    InvalidResponse(String),
+    /// A local file (eg: an attachment) could not be read
+    Io(Arc<std::io::Error>),
+    /// The request was not sent because some of its data is invalid
+    InvalidInput(String),
+    /// A message template could not be read or rendered
+    Template(String),
+    /// The message was not sent, being a duplicate of a recently sent message (see Deduplication)
+    Suppressed { key: String },
+    /// The message was not sent, being outside of the business hours of the client's schedule, until which it is deferred (see Schedule)
+    Deferred { until: SystemTime },
+    /// The API refused the application token
+    InvalidToken { request: String, errors: Vec<String> },
+    /// The API refused the user key (or the user does not exist)
+    InvalidUser { request: String, errors: Vec<String> },
+    /// The API refused the request for another reason, with the HTTP status of the refusal when known
+    Api { request: String, errors: Vec<String>, http_status: Option<u16> },
+}
+
+impl PushoverError {
+    /// Wraps the error of a custom transport
+    pub fn transport<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> PushoverError {
+        PushoverError::Transport(Arc::from(error.into()))
+    }
+
+    /// A short, stable name of the kind of error (eg: "http", "invalid_user"), usable as a metrics label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PushoverError::Http(_) => "http",
+            PushoverError::Transport(_) => "transport",
+            PushoverError::InvalidResponse(_) => "invalid_response",
+            PushoverError::Io(_) => "io",
+            PushoverError::InvalidInput(_) => "invalid_input",
+            PushoverError::Template(_) => "template",
+            PushoverError::Suppressed { .. } => "suppressed",
+            PushoverError::Deferred { .. } => "deferred",
+            PushoverError::InvalidToken { .. } => "invalid_token",
+            PushoverError::InvalidUser { .. } => "invalid_user",
+            PushoverError::Api { .. } => "api",
+        }
+    }
+
+    /// Builds the most specific error out of the fields of a refused (status 0) API response
+    pub(crate) fn from_api_response(request: String, token: Option<&str>, user: Option<&str>, errors: Option<Vec<String>>) -> PushoverError {
+        let errors: Vec<String> = errors.unwrap_or_default();
+
+        if token == Some("invalid") {
+            return PushoverError::InvalidToken { request, errors };
+        }
+
+        if user == Some("invalid") {
+            return PushoverError::InvalidUser { request, errors };
+        }
+
+        PushoverError::Api { request, errors, http_status: None }
+    }
+
+    /// Sets the HTTP status of a refusal of the API, received with the given response
+    pub(crate) fn with_http_status(self, status: u16) -> PushoverError {
+        match self {
+            PushoverError::Api { request, errors, .. } => PushoverError::Api { request, errors, http_status: Some(status) },
+            other => other,
+        }
+    }
+
+    /// Whether the request may go through if sent again later: the API could not be reached, was unavailable,
+    /// or rate limited the application
+    pub fn is_temporary(&self) -> bool {
+        match self {
+            PushoverError::Http(_) | PushoverError::Transport(_) | PushoverError::Io(_) => true,
+            PushoverError::Api { http_status: Some(status), .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<PushoverResponse> for PushoverError {
+    fn from(response: PushoverResponse) -> Self {
+        PushoverError::from_api_response(response.request, response.token.as_deref(), response.user.as_deref(), response.errors)
+    }
+}
+
+impl From<reqwest::Error> for PushoverError {
+    fn from(error: reqwest::Error) -> Self {
+        PushoverError::Http(Arc::new(error))
+    }
+}
+
+impl From<std::io::Error> for PushoverError {
+    fn from(error: std::io::Error) -> Self {
+        PushoverError::Io(Arc::new(error))
+    }
+}
+
+impl std::fmt::Display for PushoverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PushoverError::Http(error) => write!(f, "HTTP error: {}", error),
+            PushoverError::Transport(error) => write!(f, "Transport error: {}", error),
+            PushoverError::InvalidResponse(reason) => write!(f, "Invalid response: {}", reason),
+            PushoverError::Io(error) => write!(f, "I/O error: {}", error),
+            PushoverError::InvalidInput(reason) => write!(f, "Invalid input: {}", reason),
+            PushoverError::Template(reason) => write!(f, "Template error: {}", reason),
+            PushoverError::Suppressed { key } => write!(f, "Suppressed duplicate message: {:?}", key),
+            PushoverError::Deferred { until } => write!(f, "Message deferred until {:?}", until),
+            PushoverError::InvalidToken { errors, .. } => write!(f, "Invalid application token: {}", errors.join(", ")),
+            PushoverError::InvalidUser { errors, .. } => write!(f, "Invalid user: {}", errors.join(", ")),
+            PushoverError::Api { errors, .. } => write!(f, "Request refused: {}", errors.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for PushoverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PushoverError::Http(error) => Some(error.as_ref()),
+            PushoverError::Transport(error) => Some(error.as_ref()),
+            PushoverError::Io(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
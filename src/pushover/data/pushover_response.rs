@@ -1,5 +1,8 @@
 use serde::Deserialize;
 
+use crate::transport::TransportResponse;
+use super::PushoverError;
+
 /**
  Data of the response given by the Pushover API. (if any) 
  **/
@@ -18,26 +21,59 @@ pub struct PushoverResponse {
     pub token: Option<String>,
     /// A list of error messages
     pub errors: Option<Vec<String>>,
+    /// The receipt of an emergency priority message, used to cancel its retries
+    pub receipt: Option<String>,
 }
 
 impl PushoverResponse {
-    pub async fn try_from_reqwest_response(response: reqwest::Response) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
-        let data = response.json::<PushoverResponse>().await;
-
-        if data.is_err() {
-            return Err(Box::from(data.err().unwrap()))
+    /// The response returned instead of sending a request, in dry-run mode
+    pub(crate) fn dry_run() -> PushoverResponse {
+        PushoverResponse {
+            status: 1,
+            request: "dry-run".into(),
+            user: None,
+            token: None,
+            errors: None,
+            receipt: None,
         }
+    }
 
-        return Ok(data.ok().unwrap());
+    pub async fn try_from_reqwest_response(response: reqwest::Response) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
+        Ok(response.json::<PushoverResponse>().await?)
     }
 
+    #[cfg(feature = "blocking")]
     pub fn try_from_blocking_reqwest_response(response: reqwest::blocking::Response) -> Result<PushoverResponse, Box<dyn std::error::Error>> {
-        let data = response.json::<PushoverResponse>();
+        Ok(response.json::<PushoverResponse>()?)
+    }
+
+    /// Turns back a refusal of the API into the refused (status 0) response, other errors are kept
+    pub(crate) fn from_refusal(error: PushoverError) -> Result<PushoverResponse, PushoverError> {
+        let (request, token, user, errors) = match error {
+            PushoverError::InvalidToken { request, errors } => (request, Some("invalid".to_owned()), None, errors),
+            PushoverError::InvalidUser { request, errors } => (request, None, Some("invalid".to_owned()), errors),
+            PushoverError::Api { request, errors, .. } => (request, None, None, errors),
+            other => return Err(other),
+        };
+
+        Ok(PushoverResponse {
+            status: 0,
+            request,
+            user,
+            token,
+            errors: Some(errors),
+            receipt: None,
+        })
+    }
+
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<PushoverResponse, PushoverError> {
+        let data: PushoverResponse = response.json::<PushoverResponse>()?;
 
-        if data.is_err() {
-            return Err(Box::from(data.err().unwrap()))
+        if data.status != 1 {
+            return Err(PushoverError::from(data));
         }
 
-        return Ok(data.ok().unwrap());
+        Ok(data)
     }
 }
@@ -1,8 +1,7 @@
-#[allow(dead_code)]
-
 /**
  An enum of all the officially available notification sounds in Pushover. 
  **/
+#[allow(dead_code)]
 pub enum PushoverSound {
     PUSHOVER,
     BIKE,
@@ -61,3 +60,39 @@ impl std::fmt::Display for PushoverSound {
         write!(f, "{}", name)
     }
 }
+
+// Enables "[soundname]".parse::<PushoverSound>();
+impl std::str::FromStr for PushoverSound {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let sound: PushoverSound = match name.trim().to_lowercase().as_str() {
+            "pushover" => PushoverSound::PUSHOVER,
+            "bike" => PushoverSound::BIKE,
+            "bugle" => PushoverSound::BUGLE,
+            "cashregister" => PushoverSound::CASHREGISTER,
+            "classical" => PushoverSound::CLASSICAL,
+            "cosmic" => PushoverSound::COSMIC,
+            "falling" => PushoverSound::FALLING,
+            "gamelan" => PushoverSound::GAMELAN,
+            "incoming" => PushoverSound::INCOMING,
+            "intermission" => PushoverSound::INTERMISSION,
+            "magic" => PushoverSound::MAGIC,
+            "mechanical" => PushoverSound::MECHANICAL,
+            "pianobar" => PushoverSound::PIANOBAR,
+            "siren" => PushoverSound::SIREN,
+            "spacealarm" => PushoverSound::SPACEALARM,
+            "tugboat" => PushoverSound::TUGBOAT,
+            "alien" => PushoverSound::ALIEN,
+            "climb" => PushoverSound::CLIMB,
+            "persistent" => PushoverSound::PERSISTENT,
+            "echo" => PushoverSound::ECHO,
+            "updown" => PushoverSound::UPDOWN,
+            "vibrate" => PushoverSound::VIBRATE,
+            "none" => PushoverSound::NONE,
+            _ => return Err(format!("Unknown sound: {}", name)),
+        };
+
+        Ok(sound)
+    }
+}
@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+use crate::transport::TransportResponse;
+use super::PushoverError;
+
+/**
+ Data of the response given by the Pushover API when polling the receipt of an emergency priority message.
+
+ Times are Unix timestamps, 0 when the event did not happen (yet).
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptResponse {
+    /* Always present in response */
+    /// 1 if the request was valid, 0 otherwise. (Not the HTTP status)
+    pub status: u8,
+    /// The request GUID
+    pub request: String,
+
+    /* Present once the receipt is found */
+    /// 1 if the user acknowledged the message
+    #[serde(default)]
+    pub acknowledged: u8,
+    #[serde(default)]
+    pub acknowledged_at: u64,
+    /// The user key of the user who acknowledged the message
+    #[serde(default)]
+    pub acknowledged_by: String,
+    /// The name of the device the message was acknowledged on
+    #[serde(default)]
+    pub acknowledged_by_device: String,
+    /// The last time the message was delivered to a device
+    #[serde(default)]
+    pub last_delivered_at: u64,
+    /// 1 if the retries stopped, either expired or cancelled
+    #[serde(default)]
+    pub expired: u8,
+    /// When the retries stop
+    #[serde(default)]
+    pub expires_at: u64,
+    /// 1 if the callback URL was called
+    #[serde(default)]
+    pub called_back: u8,
+    #[serde(default)]
+    pub called_back_at: u64,
+
+    /* Not always present */
+    /// Specifies a "app token" error type
+    pub token: Option<String>,
+    /// Specifies a "receipt" error type
+    pub receipt: Option<String>,
+    /// A list of error messages
+    pub errors: Option<Vec<String>>,
+}
+
+impl ReceiptResponse {
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<ReceiptResponse, PushoverError> {
+        let data: ReceiptResponse = response.json::<ReceiptResponse>()?;
+
+        if data.status != 1 {
+            return Err(PushoverError::from_api_response(data.request, data.token.as_deref(), None, data.errors));
+        }
+
+        Ok(data)
+    }
+
+    /// Whether the user acknowledged the message
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged == 1
+    }
+}
@@ -0,0 +1,75 @@
+use crate::pushover::constants::PUSHOVER_API_MAX_RECIPIENTS_PER_REQUEST;
+
+/**
+A list of user and/or group keys to send a same message to, to be used in conjunction with the send_pushover_request_to_recipients function.
+
+Keys are sent in batches of up to 50 keys per request.
+ **/
+#[derive(Debug, Clone, Default)]
+pub struct Recipients {
+    keys: Vec<String>,
+    individual_fallback: bool,
+}
+
+impl Recipients {
+    /// Creates an empty list of recipients
+    pub fn new() -> Self {
+        Recipients::default()
+    }
+
+    /// Adds a user or group key. Empty and already added keys are ignored.
+    pub fn add_key(mut self, key: &str) -> Recipients {
+        let key: &str = key.trim();
+        if key.is_empty() || self.keys.iter().any(|existing| existing == key) {
+            return self;
+        }
+
+        self.keys.push(key.to_owned());
+        self
+    }
+
+    /// Adds many user or group keys at once. Empty and already added keys are ignored.
+    pub fn add_keys<I, S>(self, keys: I) -> Recipients
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        keys.into_iter().fold(self, |recipients, key| recipients.add_key(key.as_ref()))
+    }
+
+    /// When enabled, a batch whose user keys are refused by the API is sent again once per recipient,
+    /// so that a single bad key doesn't make the whole batch fail.
+    pub fn set_individual_fallback(mut self, individual_fallback: bool) -> Recipients {
+        self.individual_fallback = individual_fallback;
+        self
+    }
+
+    /// Whether refused batches are sent again once per recipient
+    pub fn has_individual_fallback(&self) -> bool {
+        self.individual_fallback
+    }
+
+    /// The user and group keys, in the order they were added
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// The keys, split in batches of at most 50 keys (the API's limit for a single request)
+    pub fn batches(&self) -> std::slice::Chunks<'_, String> {
+        self.keys.chunks(PUSHOVER_API_MAX_RECIPIENTS_PER_REQUEST)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for Recipients {
+    fn from_iter<I: IntoIterator<Item = S>>(keys: I) -> Self {
+        Recipients::new().add_keys(keys)
+    }
+}
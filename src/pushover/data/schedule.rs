@@ -0,0 +1,121 @@
+use chrono::{DateTime, Datelike, Days, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use super::{Message, Priority};
+
+/**
+A server-side delivery policy, based on business hours in a time zone. (Requires the `schedule` feature)
+
+During business hours, messages are sent as is. Outside of them, non-critical messages (priority below 2)
+get their priority lowered, or are held until the business hours start. Emergency messages (priority 2) are never touched.
+
+By default, business hours are from 09:00 to 18:00, Monday to Friday, and priorities are lowered to Low (quiet) outside of them.
+ **/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// The time zone of the business hours
+    pub timezone: Tz,
+    /// The days having business hours
+    pub business_days: Vec<Weekday>,
+    /// The start of the business hours
+    pub start: NaiveTime,
+    /// The end of the business hours (excluded), must be after the start
+    pub end: NaiveTime,
+    /// What happens to non-critical messages outside of business hours
+    pub off_hours: OffHours,
+}
+
+/**
+What happens to non-critical messages outside of business hours.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OffHours {
+    /// Lowers the priority of the messages to at most this one (eg: High -> Low)
+    LowerPriority(Priority),
+    /// Holds the messages until the business hours start
+    Defer,
+}
+
+/**
+What a schedule decided for a message.
+ **/
+#[derive(Debug, Clone)]
+pub enum ScheduleDecision {
+    /// Send this (possibly rewritten) message now
+    Send(Message),
+    /// Send this message once the business hours start
+    Defer { until: DateTime<Utc>, message: Message },
+}
+
+impl Schedule {
+    /// Creates a schedule with business hours from 09:00 to 18:00, Monday to Friday, in the given time zone
+    pub fn new(timezone: Tz) -> Self {
+        Self {
+            timezone,
+            business_days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            start: NaiveTime::from_hms_opt(9, 0, 0).expect("09:00 is a valid time"),
+            end: NaiveTime::from_hms_opt(18, 0, 0).expect("18:00 is a valid time"),
+            off_hours: OffHours::LowerPriority(Priority::Low),
+        }
+    }
+
+    /// Sets the days having business hours
+    pub fn set_business_days(mut self, business_days: &[Weekday]) -> Schedule {
+        self.business_days = business_days.to_vec();
+        self
+    }
+
+    /// Sets the business hours of every business day, the end being excluded
+    pub fn set_business_hours(mut self, start: NaiveTime, end: NaiveTime) -> Schedule {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Sets what happens to non-critical messages outside of business hours
+    pub fn set_off_hours(mut self, off_hours: OffHours) -> Schedule {
+        self.off_hours = off_hours;
+        self
+    }
+
+    /// Whether the given instant is within business hours
+    pub fn is_business_time(&self, at: DateTime<Utc>) -> bool {
+        let local: DateTime<Tz> = at.with_timezone(&self.timezone);
+        let time: NaiveTime = local.time();
+        self.business_days.contains(&local.weekday()) && self.start <= time && time < self.end
+    }
+
+    /// The next start of business hours after the given instant, if there are business days
+    pub fn next_opening(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = after.with_timezone(&self.timezone).date_naive();
+
+        (0..=7)
+            .filter_map(|days| today.checked_add_days(Days::new(days)))
+            .filter(|day| self.business_days.contains(&day.weekday()))
+            // A start skipped by a daylight saving time change has no opening that day
+            .filter_map(|day| self.timezone.from_local_datetime(&day.and_time(self.start)).earliest())
+            .map(|opening| opening.with_timezone(&Utc))
+            .find(|opening| *opening > after)
+    }
+
+    /// Decides what to do with a message sent at the given instant
+    pub fn apply(&self, message: &Message, at: DateTime<Utc>) -> ScheduleDecision {
+        let priority: i8 = message.priority.unwrap_or(0);
+        if priority >= 2 || self.is_business_time(at) {
+            return ScheduleDecision::Send(message.clone());
+        }
+
+        match &self.off_hours {
+            OffHours::LowerPriority(lowered) if priority > lowered.level() => {
+                let mut message: Message = message.clone();
+                message.priority = Some(lowered.level());
+                ScheduleDecision::Send(message)
+            },
+            OffHours::LowerPriority(_) => ScheduleDecision::Send(message.clone()),
+            OffHours::Defer => match self.next_opening(at) {
+                Some(until) => ScheduleDecision::Defer { until, message: message.clone() },
+                None => ScheduleDecision::Send(message.clone()),
+            },
+        }
+    }
+}
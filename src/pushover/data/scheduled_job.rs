@@ -0,0 +1,50 @@
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Message, PushoverError};
+
+/**
+A message waiting to be sent by a Scheduler. (Requires the `scheduler` feature)
+ **/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    /// The id of the job, used to cancel it
+    pub id: String,
+    /// The message to send
+    pub message: Message,
+    /// When the message is sent
+    pub trigger: Trigger,
+    /// The next time the message will be sent
+    pub next_run: SystemTime,
+}
+
+/**
+When a scheduled message is sent.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    /// Once, at this time
+    At(SystemTime),
+    /// Repeatedly, following a cron expression evaluated in UTC.
+    ///
+    /// The expression starts with the seconds: `sec min hour day-of-month month day-of-week [year]`, eg: "0 30 9 * * Mon-Fri"
+    Cron(String),
+}
+
+impl Trigger {
+    /// The first time the trigger fires strictly after the given time, None if it never fires again
+    pub fn next_after(&self, after: SystemTime) -> Result<Option<SystemTime>, PushoverError> {
+        match self {
+            Trigger::At(at) => Ok((*at > after).then_some(*at)),
+            Trigger::Cron(expression) => {
+                let schedule: cron::Schedule = cron::Schedule::from_str(expression)
+                    .map_err(|error| PushoverError::InvalidInput(format!("Invalid cron expression \"{}\": {}", expression, error)))?;
+                let after: DateTime<Utc> = after.into();
+                Ok(schedule.after(&after).next().map(SystemTime::from))
+            },
+        }
+    }
+}
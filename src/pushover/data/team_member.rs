@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+/**
+ A member of a Pushover for Teams team, as listed by the teams API.
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct TeamMember {
+    /// The member's display name
+    pub name: String,
+    /// The member's e-mail address
+    pub email: String,
+    /// The member's user key, usable as a message recipient
+    #[serde(rename = "user")]
+    pub user_key: String,
+    /// Whether the member is an administrator of the team
+    #[serde(default)]
+    pub admin: bool,
+    /// The names of the member's registered devices
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// The names of the delivery groups the member belongs to
+    #[serde(default)]
+    pub groups: Vec<String>,
+}
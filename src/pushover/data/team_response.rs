@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+use crate::transport::TransportResponse;
+use super::{PushoverError, TeamMember};
+
+/**
+ Data of the response given by the Pushover teams API when showing a team.
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct TeamResponse {
+    /* Always present in response */
+    /// 1 if the request was valid, 0 otherwise. (Not the HTTP status)
+    pub status: u8,
+    /// The request GUID
+    pub request: String,
+
+    /* Not always present */
+    /// The name of the team
+    pub name: Option<String>,
+    /// The members of the team
+    #[serde(default)]
+    pub users: Vec<TeamMember>,
+    /// Specifies a "team token" error type
+    pub token: Option<String>,
+    /// A list of error messages
+    pub errors: Option<Vec<String>>,
+}
+
+impl TeamResponse {
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<TeamResponse, PushoverError> {
+        response.json::<TeamResponse>()?.checked()
+    }
+
+    pub(crate) fn checked(self) -> Result<TeamResponse, PushoverError> {
+        if self.status != 1 {
+            return Err(PushoverError::from_api_response(self.request, self.token.as_deref(), None, self.errors));
+        }
+
+        Ok(self)
+    }
+}
@@ -0,0 +1,47 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+/**
+A user to be added to a team, to be used in conjunction with the teams::add_team_user function.
+
+Note: It is preferred to create a TeamUser through the TeamUserBuilder.
+ **/
+pub struct TeamUser {
+    /* Required */
+    /// (Required) Your team API token, see https://pushover.net/teams (not an application token)
+    #[serde(rename = "token")]
+    pub team_token: String,
+    /// (Required) The e-mail address of the user to add
+    pub email: String,
+
+    /* Optional */
+    /// The user's name, used when a new account has to be created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// A password for the new account, otherwise the user will have to set one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Send as true to skip the invitation e-mail and activate the account right away
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instant: Option<bool>,
+    /// Send as true to make the user an administrator of the team
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<bool>,
+    /// The name of a delivery group of the team to add the user to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl Default for TeamUser {
+    fn default() -> Self {
+        Self {
+            team_token: "".into(),
+            email: "".into(),
+            name: None,
+            password: None,
+            instant: None,
+            admin: None,
+            group: None,
+        }
+    }
+}
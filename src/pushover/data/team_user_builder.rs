@@ -0,0 +1,78 @@
+use super::TeamUser;
+
+/**
+Helps build a correct Pushover teams "add user" request.
+ */
+#[derive(Debug)]
+pub struct TeamUserBuilder {
+    build: TeamUser,
+}
+
+impl TeamUserBuilder {
+    /// Creates a new TeamUserBuilder instance with the required minimal informations (Team token & E-mail)
+    pub fn new(team_token: &str, email: &str) -> Self {
+        TeamUserBuilder {
+            build: TeamUser {
+                team_token: team_token.to_owned(),
+                email: email.to_owned(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the name of the user
+    pub fn set_name(mut self, name: &str) -> TeamUserBuilder {
+        if name.trim().is_empty() {
+            self.build.name = None;
+            return self;
+        }
+
+        self.build.name = Some(name.to_owned());
+        self
+    }
+
+    /// Sets the password of the new account
+    pub fn set_password(mut self, password: &str) -> TeamUserBuilder {
+        if password.is_empty() {
+            self.build.password = None;
+            return self;
+        }
+
+        self.build.password = Some(password.to_owned());
+        self
+    }
+
+    /// Activates the account right away instead of sending an invitation e-mail
+    pub fn set_instant(mut self, instant: bool) -> TeamUserBuilder {
+        self.build.instant = Some(instant);
+        self
+    }
+
+    /// Makes the user an administrator of the team
+    pub fn set_admin(mut self, admin: bool) -> TeamUserBuilder {
+        self.build.admin = Some(admin);
+        self
+    }
+
+    /// Adds the user to a delivery group of the team
+    pub fn set_group(mut self, group: &str) -> TeamUserBuilder {
+        if group.trim().is_empty() {
+            self.build.group = None;
+            return self;
+        }
+
+        self.build.group = Some(group.to_owned());
+        self
+    }
+
+    /// Removes the delivery group
+    pub fn remove_group(mut self) -> TeamUserBuilder {
+        self.build.group = None;
+        self
+    }
+
+    /// Transforms the TeamUserBuilder into a usable TeamUser
+    pub fn build(self) -> TeamUser {
+        self.build
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/**
+ A point in time, usable as the timestamp of a message: a SystemTime,
+ a chrono DateTime (`chrono` feature) or a time OffsetDateTime (`time` feature).
+ **/
+pub trait IntoTimestamp {
+    /// Seconds since the Unix epoch, None for earlier times
+    fn unix_timestamp(&self) -> Option<u64>;
+}
+
+impl IntoTimestamp for SystemTime {
+    fn unix_timestamp(&self) -> Option<u64> {
+        self.duration_since(UNIX_EPOCH).ok().map(|since_epoch| since_epoch.as_secs())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> IntoTimestamp for chrono::DateTime<Tz> {
+    fn unix_timestamp(&self) -> Option<u64> {
+        u64::try_from(self.timestamp()).ok()
+    }
+}
+
+#[cfg(feature = "time")]
+impl IntoTimestamp for time::OffsetDateTime {
+    fn unix_timestamp(&self) -> Option<u64> {
+        u64::try_from(time::OffsetDateTime::unix_timestamp(*self)).ok()
+    }
+}
+
+/**
+ A duration, usable as the TTL, retry or expire of a message: a std Duration,
+ a chrono TimeDelta (`chrono` feature) or a time Duration (`time` feature). Negative durations count as zero.
+ **/
+pub trait IntoDuration {
+    fn into_duration(self) -> Duration;
+}
+
+impl IntoDuration for Duration {
+    fn into_duration(self) -> Duration {
+        self
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoDuration for chrono::TimeDelta {
+    fn into_duration(self) -> Duration {
+        self.to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+#[cfg(feature = "time")]
+impl IntoDuration for time::Duration {
+    fn into_duration(self) -> Duration {
+        Duration::try_from(self).unwrap_or(Duration::ZERO)
+    }
+}
+
+/// The seconds of a duration as sent to the API, a started second counting as a whole one
+pub(crate) fn whole_seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
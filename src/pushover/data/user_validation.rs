@@ -0,0 +1,45 @@
+use serde::Deserialize;
+
+use crate::transport::TransportResponse;
+use super::PushoverError;
+
+/**
+ Data of the response given by the Pushover API when validating a user or group key.
+ **/
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserValidationResponse {
+    /* Always present in response */
+    /// 1 if the user (and device, if given) is valid, 0 otherwise. (Not the HTTP status)
+    pub status: u8,
+    /// The request GUID
+    pub request: String,
+
+    /* Not always present */
+    /// 1 if the key is a group's key
+    pub group: Option<u8>,
+    /// The names of the user's active devices
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// The platforms the user has a license for, eg: "Android", "iOS", "Desktop"
+    #[serde(default)]
+    pub licenses: Vec<String>,
+    /// Specifies a "user key" error type
+    pub user: Option<String>,
+    /// Specifies a "app token" error type
+    pub token: Option<String>,
+    /// A list of error messages
+    pub errors: Option<Vec<String>>,
+}
+
+impl UserValidationResponse {
+    /// Reads the response received by a transport. A request refused by the API (status 0) is returned as an error.
+    pub fn try_from_transport_response(response: &TransportResponse) -> Result<UserValidationResponse, PushoverError> {
+        let data: UserValidationResponse = response.json::<UserValidationResponse>()?;
+
+        if data.status != 1 {
+            return Err(PushoverError::from_api_response(data.request, data.token.as_deref(), data.user.as_deref(), data.errors));
+        }
+
+        Ok(data)
+    }
+}
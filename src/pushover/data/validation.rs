@@ -0,0 +1,74 @@
+use crate::pushover::constants::{PUSHOVER_API_MAX_EXPIRE_SECS, PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::constants::{PUSHOVER_API_MIN_EXPIRE_SECS, PUSHOVER_API_MIN_RETRY_SECS};
+use super::PushoverError;
+
+pub(crate) fn check_required(name: &str, value: &str) -> Result<(), PushoverError> {
+    if value.trim().is_empty() {
+        return Err(PushoverError::InvalidInput(format!("The {} is empty", name)));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_message(message: &str) -> Result<(), PushoverError> {
+    check_required("message", message)?;
+    check_length("message", message, PUSHOVER_API_MAX_MESSAGE_LENGTH)
+}
+
+pub(crate) fn check_title(title: Option<&str>) -> Result<(), PushoverError> {
+    match title {
+        Some(title) => check_length("title", title, PUSHOVER_API_MAX_TITLE_LENGTH),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn check_priority(priority: Option<i8>) -> Result<(), PushoverError> {
+    match priority {
+        Some(priority) if !(-2..=2).contains(&priority) => {
+            Err(PushoverError::InvalidInput(format!("The priority must be between -2 and 2, not {}", priority)))
+        },
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_retry(retry_secs: Option<i32>) -> Result<(), PushoverError> {
+    match retry_secs {
+        Some(retry_secs) if retry_secs < PUSHOVER_API_MIN_RETRY_SECS as i32 => {
+            Err(PushoverError::InvalidInput(format!("The retry must be at least 30 seconds, not {}", retry_secs)))
+        },
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_expire(expire_secs: Option<i32>) -> Result<(), PushoverError> {
+    match expire_secs {
+        Some(expire_secs) if !(PUSHOVER_API_MIN_EXPIRE_SECS as i32..=PUSHOVER_API_MAX_EXPIRE_SECS as i32).contains(&expire_secs) => {
+            Err(PushoverError::InvalidInput(format!("The expire must be between 60 and 10800 seconds, not {}", expire_secs)))
+        },
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_emergency_only(priority: Option<i8>, retry: Option<i32>, expire: Option<i32>, callback: &Option<String>, tags: &Option<String>) -> Result<(), PushoverError> {
+    if priority == Some(2) && (retry.is_none() || expire.is_none()) {
+        return Err(PushoverError::InvalidInput("Emergency messages (priority 2) require a retry and an expire".into()));
+    }
+    if priority != Some(2) && (retry.is_some() || expire.is_some() || callback.is_some() || tags.is_some()) {
+        return Err(PushoverError::InvalidInput("The retry, expire, callback and tags are only used by emergency messages (priority 2)".into()));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_ttl(ttl_secs: Option<u32>) -> Result<(), PushoverError> {
+    if ttl_secs == Some(0) {
+        return Err(PushoverError::InvalidInput("The TTL must be greater than 0".into()));
+    }
+    Ok(())
+}
+
+fn check_length(name: &str, text: &str, max_chars: usize) -> Result<(), PushoverError> {
+    let length: usize = text.chars().count();
+    if length > max_chars {
+        return Err(PushoverError::InvalidInput(format!("The {} is too long ({} > {} characters)", name, length, max_chars)));
+    }
+    Ok(())
+}
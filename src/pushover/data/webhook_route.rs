@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Priority, PushoverError};
+
+/**
+The configuration of a webhook gateway, loadable from TOML (`toml` feature) or YAML (`yaml` feature):
+```toml
+user_key = "<USER_KEY>"
+app_token = "<APP_TOKEN>"
+
+[[routes]]
+path = "/github"
+title = "$.repository.full_name"
+message = "$.head_commit.message"
+url = "$.compare"
+url_title = "See the changes"
+priority = { field = "$.ref", values = { "refs/heads/main" = 1 }, default = -1 }
+```
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookGatewayConfig {
+    /// The user or group receiving the messages, unless a route has its own
+    pub user_key: String,
+    /// Your app API token
+    pub app_token: String,
+    pub routes: Vec<WebhookRoute>,
+}
+
+/**
+How the webhooks received on a path are turned into messages.
+
+Text fields are either a field selector (starting with `$`, eg: `$.alerts[0].labels.alertname` or `$["dotted.key"]`), or a literal text.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookRoute {
+    /// The path of the webhook, eg: "/github"
+    pub path: String,
+    /// (Required) The message
+    pub message: String,
+    /// The title of the message, otherwise your app's name will be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A supplementary URL to show with your message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// A title for your supplementary URL, otherwise just the URL is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_title: Option<String>,
+    /// The priority of the messages, fixed or depending on a field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<PriorityRule>,
+    /// The name of one of the sounds supported by Pushover (See sound list: https://pushover.net/api#sounds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    /// Sends the messages of this route to another user or group than the gateway's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_key: Option<String>,
+}
+
+/**
+The priority of the messages of a webhook route.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PriorityRule {
+    /// Always this priority
+    Fixed(Priority),
+    /// The priority mapped to the value of a field, or the default one (Normal if not set)
+    Mapped {
+        field: String,
+        values: BTreeMap<String, Priority>,
+        #[serde(default)]
+        default: Option<Priority>,
+    },
+}
+
+impl WebhookRoute {
+    /// Creates a route on a path, with the required message (a field selector or a literal text)
+    pub fn new(path: &str, message: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            message: message.to_owned(),
+            title: None,
+            url: None,
+            url_title: None,
+            priority: None,
+            sound: None,
+            user_key: None,
+        }
+    }
+
+    /// Sets the title (a field selector or a literal text)
+    pub fn set_title(mut self, title: &str) -> WebhookRoute {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Sets the url (and optionally, the url title), each a field selector or a literal text
+    pub fn set_url(mut self, url: &str, url_title: Option<&str>) -> WebhookRoute {
+        self.url = Some(url.to_owned());
+        self.url_title = url_title.map(|url_title| url_title.to_owned());
+        self
+    }
+
+    /// Sets the priority rule
+    pub fn set_priority(mut self, priority: PriorityRule) -> WebhookRoute {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Sets the sound, by name
+    pub fn set_sound(mut self, sound: &str) -> WebhookRoute {
+        self.sound = Some(sound.to_owned());
+        self
+    }
+
+    /// Sends the messages of this route to another user or group
+    pub fn set_user_key(mut self, user_key: &str) -> WebhookRoute {
+        self.user_key = Some(user_key.to_owned());
+        self
+    }
+}
+
+impl PriorityRule {
+    /// The priority of a webhook's message
+    pub fn priority_of(&self, webhook: &Value) -> Result<Priority, PushoverError> {
+        match self {
+            PriorityRule::Fixed(priority) => Ok(priority.clone()),
+            PriorityRule::Mapped { field, values, default } => {
+                let value: Option<String> = select_text(webhook, field)?;
+                Ok(value
+                    .and_then(|value| values.get(&value).cloned())
+                    .or_else(|| default.clone())
+                    .unwrap_or_default())
+            },
+        }
+    }
+}
+
+/// Resolves a text field: the selected value (None if it is missing or null), or the literal text
+pub(crate) fn select_text(webhook: &Value, field: &str) -> Result<Option<String>, PushoverError> {
+    if !field.starts_with('$') {
+        return Ok(Some(field.to_owned()));
+    }
+
+    Ok(match select(webhook, field)? {
+        None | Some(Value::Null) => None,
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+/// Finds the value designated by a selector such as `$.alerts[0].labels["dotted.name"]`, None if it doesn't exist
+pub(crate) fn select<'a>(webhook: &'a Value, selector: &str) -> Result<Option<&'a Value>, PushoverError> {
+    let invalid = || PushoverError::InvalidInput(format!("Invalid field selector: {}", selector));
+    let mut rest: &str = selector.strip_prefix('$').ok_or_else(invalid)?;
+    let mut current: &Value = webhook;
+
+    while !rest.is_empty() {
+        let next: Option<&Value> = if let Some(after_dot) = rest.strip_prefix('.') {
+            let end: usize = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let (name, after_name) = after_dot.split_at(end);
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            rest = after_name;
+            current.get(name)
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end: usize = after_bracket.find(']').ok_or_else(invalid)?;
+            let (inside, after_inside) = after_bracket.split_at(end);
+            rest = &after_inside[1..];
+            match inside.strip_prefix('"').and_then(|quoted| quoted.strip_suffix('"')) {
+                Some(name) => current.get(name),
+                None => current.get(inside.parse::<usize>().map_err(|_| invalid())?),
+            }
+        } else {
+            return Err(invalid());
+        };
+
+        match next {
+            Some(value) => current = value,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(current))
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::pushover::data::{Deduplication, DeduplicationStats, Message, PushoverError};
+
+/// How long the count of suppressed duplicates is kept after its window closed, waiting for a message to report it
+const SUMMARY_GRACE_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Applies a deduplication policy, shared by a client and its clones
+#[derive(Debug)]
+pub struct Deduplicator {
+    policy: Deduplication,
+    state: Mutex<DeduplicatorState>,
+}
+
+#[derive(Debug, Default)]
+struct DeduplicatorState {
+    /// The open windows, by recipient and key
+    windows: HashMap<WindowKey, Window>,
+    stats: DeduplicationStats,
+}
+
+/// The recipient and key of a message, identifying its duplicates
+pub type WindowKey = (String, String);
+
+#[derive(Debug)]
+struct Window {
+    opened_at: Instant,
+    suppressed: u64,
+}
+
+impl Deduplicator {
+    pub fn new(policy: Deduplication) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(DeduplicatorState::default()),
+        }
+    }
+
+    /// Returns the message to send (with its summary, if any) and the key of its window, or an error if it is a duplicate.
+    ///
+    /// The window only opens once the message is sent, see sent: a message that could not be sent can be retried.
+    pub fn check(&self, message: &Message) -> Result<(Message, WindowKey), PushoverError> {
+        self.check_at(message, Instant::now())
+    }
+
+    /// Same as check, at the given time
+    pub(crate) fn check_at(&self, message: &Message, now: Instant) -> Result<(Message, WindowKey), PushoverError> {
+        let key: String = self.policy.key_of(message);
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        // Forget the windows that closed without suppressing anything, nothing is left to report for them,
+        // and the suppressed counts that were not reported within the grace period
+        state.windows.retain(|_, window| {
+            let kept_for: Duration = if window.suppressed > 0 { self.policy.window + SUMMARY_GRACE_PERIOD } else { self.policy.window };
+            now.duration_since(window.opened_at) < kept_for
+        });
+
+        let window_key: WindowKey = (message.user_key.clone(), key);
+        if let Some(window) = state.windows.get_mut(&window_key) {
+            if now.duration_since(window.opened_at) < self.policy.window {
+                window.suppressed += 1;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(key = window_key.1, suppressed = window.suppressed, "Suppressed a duplicate message");
+                state.stats.suppressed += 1;
+                return Err(PushoverError::Suppressed { key: window_key.1 });
+            }
+        }
+
+        let mut message: Message = message.clone();
+        match state.windows.get(&window_key) {
+            Some(previous) if self.policy.summary && previous.suppressed > 0 => {
+                let noun: &str = if previous.suppressed == 1 { "alert" } else { "alerts" };
+                message.message = format!("{}\n\n({} similar {} suppressed)", message.message, previous.suppressed, noun);
+            },
+            _ => {},
+        }
+
+        Ok((message, window_key))
+    }
+
+    /// Opens the window of a message that was sent, its duplicates being suppressed from now on
+    pub fn sent(&self, window_key: WindowKey) {
+        self.sent_at(window_key, Instant::now())
+    }
+
+    /// Same as sent, at the given time
+    pub(crate) fn sent_at(&self, window_key: WindowKey, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let previous: Option<Window> = state.windows.insert(window_key, Window { opened_at: now, suppressed: 0 });
+        state.stats.forwarded += 1;
+        if self.policy.summary && previous.is_some_and(|previous| previous.suppressed > 0) {
+            state.stats.summaries += 1;
+        }
+    }
+
+    pub fn stats(&self) -> DeduplicationStats {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).stats
+    }
+}
+
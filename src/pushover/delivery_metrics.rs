@@ -0,0 +1,59 @@
+/*!
+Metrics of the notifications sent by the clients, through the `metrics` facade (Requires the `metrics` feature)
+
+Nothing is exported unless the application installs a recorder (eg: metrics-exporter-prometheus).
+ */
+
+use std::time::Instant;
+
+use crate::pushover::data::{PushoverError, PushoverResponse};
+use crate::transport::TransportResponse;
+
+/// Messages accepted by the API, labelled by priority and endpoint
+pub const MESSAGES_SENT: &str = "pushover_messages_sent_total";
+/// Messages that could not be sent, labelled by priority, endpoint and error kind
+pub const MESSAGES_FAILED: &str = "pushover_messages_failed_total";
+/// Duration of the API calls in seconds, labelled by endpoint and HTTP status
+pub const REQUEST_DURATION: &str = "pushover_request_duration_seconds";
+/// Messages left in the application's monthly quota, as last reported by the API
+pub const RATE_LIMIT_REMAINING: &str = "pushover_rate_limit_remaining";
+
+/// The path of the URL, used as endpoint label (the query string can hold tokens)
+fn endpoint_label(url: &str) -> String {
+    let without_query: &str = url.split_once('?').map_or(url, |(endpoint, _)| endpoint);
+    let without_scheme: &str = without_query.split_once("://").map_or(without_query, |(_, rest)| rest);
+    without_scheme.find('/').map_or("/", |start| &without_scheme[start..]).to_owned()
+}
+
+fn priority_label<P: ToString>(priority: Option<P>) -> String {
+    priority.map_or_else(|| "0".to_owned(), |priority| priority.to_string())
+}
+
+/// Records the outcome of a message send
+pub fn record_send<P: ToString>(url: &str, priority: Option<P>, result: &Result<PushoverResponse, PushoverError>) {
+    let endpoint: String = endpoint_label(url);
+    let priority: String = priority_label(priority);
+
+    match result {
+        Ok(_) => metrics::counter!(MESSAGES_SENT, "priority" => priority, "endpoint" => endpoint).increment(1),
+        Err(error) => metrics::counter!(MESSAGES_FAILED, "priority" => priority, "endpoint" => endpoint, "error" => error.kind()).increment(1),
+    }
+}
+
+/// Records the latency of an API call, and the remaining quota it reports
+pub fn record_request(url: &str, result: &Result<TransportResponse, PushoverError>, started: Instant) {
+    let endpoint: String = endpoint_label(url);
+    let status: String = match result {
+        Ok(response) => response.status.to_string(),
+        Err(_) => "error".to_owned(),
+    };
+    metrics::histogram!(REQUEST_DURATION, "endpoint" => endpoint, "status" => status).record(started.elapsed().as_secs_f64());
+
+    let remaining: Option<f64> = result
+        .as_ref()
+        .ok()
+        .and_then(|response| response.header("X-Limit-App-Remaining")?.trim().parse::<f64>().ok());
+    if let Some(remaining) = remaining {
+        metrics::gauge!(RATE_LIMIT_REMAINING).set(remaining);
+    }
+}
@@ -0,0 +1,68 @@
+/*!
+tracing spans and events of the API calls (Requires the `tracing` feature)
+
+Tokens and user keys are never recorded in clear: URLs lose their query string, and keys are shortened.
+ */
+
+use std::time::Instant;
+
+use serde_json::Value;
+use tracing::field::Empty;
+use tracing::Span;
+
+use crate::pushover::data::PushoverError;
+use crate::transport::{TransportRequest, TransportResponse};
+
+/// Keeps the first characters of each (comma separated) key, enough to tell keys apart in logs
+pub fn redact(keys: &str) -> String {
+    keys.split(',')
+        .map(|key| match key.char_indices().nth(4) {
+            Some((end, _)) => format!("{}***", &key[..end]),
+            None => "***".to_owned(),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// The URL without its query string, which can hold tokens
+pub fn endpoint(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(endpoint, _)| endpoint)
+}
+
+/// The span of a single API call
+pub fn request_span(request: &TransportRequest) -> Span {
+    tracing::debug_span!(
+        "pushover.request",
+        method = %request.method,
+        endpoint = endpoint(&request.url),
+        body_bytes = request.body.len(),
+        status = Empty,
+        request_guid = Empty,
+        latency_ms = Empty,
+    )
+}
+
+/// Records the outcome of an API call in its span, and emits an event
+pub fn record_response(span: &Span, result: &Result<TransportResponse, PushoverError>, started: Instant) {
+    let latency_ms: u64 = started.elapsed().as_millis() as u64;
+    span.record("latency_ms", latency_ms);
+
+    match result {
+        Ok(response) => {
+            span.record("status", response.status);
+            let request_guid: Option<String> = serde_json::from_slice::<Value>(&response.body)
+                .ok()
+                .and_then(|body| body.get("request")?.as_str().map(|guid| guid.to_owned()));
+            if let Some(request_guid) = &request_guid {
+                span.record("request_guid", request_guid.as_str());
+            }
+
+            if response.status < 400 {
+                tracing::debug!(parent: span, status = response.status, latency_ms, "Pushover API call succeeded");
+            } else {
+                tracing::warn!(parent: span, status = response.status, latency_ms, "Pushover API call refused");
+            }
+        },
+        Err(error) => tracing::warn!(parent: span, %error, latency_ms, "Pushover API call failed"),
+    }
+}
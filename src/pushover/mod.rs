@@ -1,2 +1,13 @@
 pub mod data;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod client;
+pub mod client_core;
+pub mod pacer;
+pub mod deduplicator;
+#[cfg(feature = "tracing")]
+pub mod instrumentation;
+#[cfg(feature = "metrics")]
+pub mod delivery_metrics;
+pub mod runtime;
+#[cfg(any(feature = "blocking", feature = "webhook"))]
+pub mod text;
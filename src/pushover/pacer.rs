@@ -0,0 +1,26 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Spaces out the start of requests by a fixed interval
+#[derive(Debug)]
+pub struct Pacer {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl Pacer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Books the next free slot, the caller has to wait until the returned instant before sending
+    pub fn book_slot(&self) -> Instant {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let slot: Instant = (*next_slot).max(Instant::now());
+        *next_slot = slot + self.interval;
+        slot
+    }
+}
@@ -0,0 +1,44 @@
+/*!
+Async helpers that don't depend on a specific runtime.
+
+With the `tokio` feature, tokio's file system and timer are used. Otherwise, files are read on a helper thread
+and timers are driven by `futures-timer`, both working on any executor.
+ */
+
+use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Reads a whole file without blocking the executor
+#[cfg(feature = "tokio")]
+pub async fn read_file(path: PathBuf) -> io::Result<Vec<u8>> {
+    tokio::fs::read(path).await
+}
+
+/// Reads a whole file without blocking the executor
+#[cfg(not(feature = "tokio"))]
+pub async fn read_file(path: PathBuf) -> io::Result<Vec<u8>> {
+    let (sender, receiver) = futures_channel::oneshot::channel::<io::Result<Vec<u8>>>();
+    std::thread::spawn(move || {
+        // The receiver is only gone if the caller stopped waiting
+        let _ = sender.send(std::fs::read(path));
+    });
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(io::Error::other("File reading thread stopped unexpectedly")))
+}
+
+/// Waits until the deadline is reached
+#[cfg(feature = "tokio")]
+pub async fn sleep_until(deadline: Instant) {
+    tokio::time::sleep_until(deadline.into()).await;
+}
+
+/// Waits until the deadline is reached
+#[cfg(not(feature = "tokio"))]
+pub async fn sleep_until(deadline: Instant) {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if !remaining.is_zero() {
+        futures_timer::Delay::new(remaining).await;
+    }
+}
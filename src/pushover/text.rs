@@ -0,0 +1,7 @@
+/// Cuts a text to a number of characters, eg: to fit Pushover's length limits
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => text[..end].to_owned(),
+        None => text.to_owned(),
+    }
+}
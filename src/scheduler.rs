@@ -0,0 +1,191 @@
+/*!
+Delayed and recurring delivery of messages (Requires the `scheduler` feature)
+
+Pushover has no server-side scheduling (a message's timestamp only changes the displayed time),
+so the Scheduler keeps the pending messages and sends them through a PushoverClient when they are due.
+Pending jobs can be persisted to a JSON file, to survive restarts.
+ */
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use crate::pushover::data::{ScheduledJob, Trigger};
+use crate::pushover::runtime;
+use crate::{Message, PushoverClient, PushoverError, PushoverResponse};
+
+/// The longest time the run loop sleeps, so that newly scheduled jobs are picked up
+const SCHEDULER_MAX_SLEEP: Duration = Duration::from_secs(1);
+
+/// The result of a job, by job id
+type JobResult = (String, Result<PushoverResponse, PushoverError>);
+
+/**
+Sends messages at a given time, or repeatedly following a cron expression.
+
+Nothing is sent unless `run` is polled (eg: spawned on your runtime), or `run_pending` is called.
+ */
+#[derive(Debug)]
+pub struct Scheduler {
+    client: PushoverClient,
+    store: Option<PathBuf>,
+    jobs: Mutex<BTreeMap<String, ScheduledJob>>,
+    next_id: AtomicU64,
+}
+
+impl Scheduler {
+    /// Creates a scheduler keeping its jobs in memory only
+    pub fn new(client: PushoverClient) -> Self {
+        Self {
+            client,
+            store: None,
+            jobs: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a scheduler persisting its jobs to a JSON file, loading the jobs already in it (if the file exists)
+    pub fn with_store<P: AsRef<Path>>(client: PushoverClient, path: P) -> Result<Self, PushoverError> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let jobs: Vec<ScheduledJob> = match std::fs::read(&path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .map_err(|error| PushoverError::InvalidInput(format!("Invalid scheduler store {}: {}", path.display(), error)))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            client,
+            store: Some(path),
+            jobs: Mutex::new(jobs.into_iter().map(|job| (job.id.clone(), job)).collect()),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Schedules a message to be sent once, at the given time (immediately if it is in the past). Returns the job id.
+    pub fn schedule_at(&self, message: Message, send_at: SystemTime) -> Result<String, PushoverError> {
+        self.add_job(message, Trigger::At(send_at), send_at)
+    }
+
+    /// Schedules a message to be sent every time the cron expression fires (see Trigger::Cron). Returns the job id.
+    pub fn schedule_cron(&self, message: Message, expression: &str) -> Result<String, PushoverError> {
+        let trigger: Trigger = Trigger::Cron(expression.to_owned());
+        let next_run: SystemTime = trigger
+            .next_after(SystemTime::now())?
+            .ok_or_else(|| PushoverError::InvalidInput(format!("The cron expression \"{}\" never fires", expression)))?;
+        self.add_job(message, trigger, next_run)
+    }
+
+    /// Cancels a job. Returns false if there was no such job.
+    pub fn cancel(&self, id: &str) -> Result<bool, PushoverError> {
+        let mut jobs = self.lock_jobs();
+        if jobs.remove(id).is_none() {
+            return Ok(false);
+        }
+
+        self.persist(&jobs)?;
+        Ok(true)
+    }
+
+    /// Returns the pending jobs
+    pub fn jobs(&self) -> Vec<ScheduledJob> {
+        self.lock_jobs().values().cloned().collect()
+    }
+
+    /// Sends the messages that are due, and returns the result of each by job id.
+    ///
+    /// One-time jobs are removed once sent (even if sending failed), cron jobs are rescheduled.
+    /// A job whose next run cannot be computed is removed without being sent, its result being the error.
+    pub async fn run_pending(&self) -> Result<Vec<JobResult>, PushoverError> {
+        let now: SystemTime = SystemTime::now();
+        let (due, failed): (Vec<ScheduledJob>, Vec<JobResult>) = {
+            let mut jobs = self.lock_jobs();
+            // All the next runs are computed before any job is changed
+            let next_runs: Vec<(ScheduledJob, Result<Option<SystemTime>, PushoverError>)> = jobs.values()
+                .filter(|job| job.next_run <= now)
+                .map(|job| (job.clone(), job.trigger.next_after(now)))
+                .collect();
+
+            let mut due: Vec<ScheduledJob> = Vec::with_capacity(next_runs.len());
+            let mut failed: Vec<JobResult> = Vec::new();
+            for (job, next_run) in next_runs {
+                match next_run {
+                    Ok(Some(next_run)) => {
+                        if let Some(kept) = jobs.get_mut(&job.id) {
+                            kept.next_run = next_run;
+                        }
+                        due.push(job);
+                    },
+                    Ok(None) => {
+                        jobs.remove(&job.id);
+                        due.push(job);
+                    },
+                    Err(error) => {
+                        jobs.remove(&job.id);
+                        failed.push((job.id, Err(error)));
+                    },
+                }
+            }
+
+            if !due.is_empty() || !failed.is_empty() {
+                self.persist(&jobs)?;
+            }
+            (due, failed)
+        };
+
+        let results = self.client.send_all(due.iter().map(|job| job.message.clone())).await;
+        Ok(due.into_iter().map(|job| job.id).zip(results).chain(failed).collect())
+    }
+
+    /// Sends the messages when they are due, forever. Failed sends are not retried.
+    ///
+    /// Returns only if the jobs could not be persisted.
+    pub async fn run(&self) -> Result<(), PushoverError> {
+        loop {
+            self.run_pending().await?;
+
+            let now: SystemTime = SystemTime::now();
+            let until_next: Duration = self.lock_jobs()
+                .values()
+                .map(|job| job.next_run.duration_since(now).unwrap_or_default())
+                .min()
+                .unwrap_or(SCHEDULER_MAX_SLEEP)
+                .min(SCHEDULER_MAX_SLEEP);
+            runtime::sleep_until(Instant::now() + until_next).await;
+        }
+    }
+
+    fn add_job(&self, message: Message, trigger: Trigger, next_run: SystemTime) -> Result<String, PushoverError> {
+        let id: String = self.new_id();
+        let mut jobs = self.lock_jobs();
+        jobs.insert(id.clone(), ScheduledJob { id: id.clone(), message, trigger, next_run });
+        self.persist(&jobs)?;
+        Ok(id)
+    }
+
+    /// Generates an id that is unique in this process, and unlikely to clash with the ids of previous runs
+    fn new_id(&self) -> String {
+        let started: u128 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        format!("{:x}-{}", started, self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, ScheduledJob>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Writes the jobs to the store (if any), replacing the previous file at once
+    fn persist(&self, jobs: &BTreeMap<String, ScheduledJob>) -> Result<(), PushoverError> {
+        let Some(path) = &self.store else {
+            return Ok(());
+        };
+
+        let content: Vec<u8> = serde_json::to_vec_pretty(&jobs.values().collect::<Vec<&ScheduledJob>>())
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot serialize the scheduled jobs: {}", error)))?;
+        let temporary: PathBuf = path.with_extension("tmp");
+        std::fs::write(&temporary, content)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+/*!
+An e-mail to Pushover bridge: a lightweight SMTP listener forwarding the e-mails it receives (Requires the `smtp` feature)
+
+Meant to run locally, for tools that can only notify by e-mail (NAS, printers, cron, legacy monitoring).
+It doesn't authenticate, encrypt nor relay anything else: don't expose it beyond a trusted network.
+
+```no_run
+use pushover_rs::smtp::{SmtpBridge, SmtpRecipient};
+
+SmtpBridge::new("<USER_KEY>", "<APP_TOKEN>")
+    .set_recipient("phone", SmtpRecipient::Device("iphone".to_owned()))
+    .set_recipient("oncall", SmtpRecipient::UserKey("<GROUP_KEY>".to_owned()))
+    .listen("127.0.0.1:2525")
+    .unwrap();
+```
+ */
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use mail_parser::{MessageParser, MessagePart, MimeHeaders};
+
+use crate::blocking::PushoverClient;
+use crate::pushover::constants::{PUSHOVER_API_ATTACHMENT_MAX_SIZE_BYTES, PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::text::truncate;
+use crate::{AttachmentMessageBuilder, MessageBuilder, PushoverError, PushoverResponse};
+
+/// By default, e-mails larger than this are refused (the attachment limit, plus room for the encoding)
+const SMTP_DEFAULT_MAX_SIZE_BYTES: usize = 4 * 1024 * 1024;
+/// Recipients accepted per e-mail
+const SMTP_MAX_RECIPIENTS: usize = 100;
+/// Length of a command line, as per RFC 5321
+const SMTP_MAX_COMMAND_LENGTH: u64 = 512;
+/// Idle connections are closed after this long
+const SMTP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Distinguishes the temporary attachment files of a process
+static NEXT_ATTACHMENT_ID: AtomicU64 = AtomicU64::new(0);
+
+/**
+Where the e-mails sent to a local part (the part of the address before the "@") are forwarded.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmtpRecipient {
+    /// A device of the bridge's user
+    Device(String),
+    /// Another user or group key, on all its devices
+    UserKey(String),
+}
+
+/**
+Accepts e-mails over SMTP and sends them as Pushover messages.
+
+The subject is the title, the text body is the message, and the first image attachment (up to 2.5MB) is attached.
+E-mails are forwarded to the recipients set for their addresses' local parts (compared case-insensitively),
+the other addresses go to the bridge's user, on all its devices.
+ **/
+#[derive(Debug)]
+pub struct SmtpBridge {
+    user_key: String,
+    app_token: String,
+    recipients: HashMap<String, SmtpRecipient>,
+    max_size: usize,
+    client: PushoverClient,
+}
+
+impl SmtpBridge {
+    /// Creates a bridge sending the e-mails to this user or group
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        Self {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            recipients: HashMap::new(),
+            max_size: SMTP_DEFAULT_MAX_SIZE_BYTES,
+            client: PushoverClient::new(),
+        }
+    }
+
+    /// Sends the messages through this client
+    pub fn set_client(mut self, client: PushoverClient) -> SmtpBridge {
+        self.client = client;
+        self
+    }
+
+    /// Forwards the e-mails sent to a local part (eg: "phone" for "phone@localhost") to a device or another user
+    pub fn set_recipient(mut self, local_part: &str, recipient: SmtpRecipient) -> SmtpBridge {
+        self.recipients.insert(local_part.to_lowercase(), recipient);
+        self
+    }
+
+    /// Sets the size of the largest accepted e-mail, in bytes (Default: 4MB)
+    pub fn set_max_size(mut self, max_size_bytes: usize) -> SmtpBridge {
+        self.max_size = max_size_bytes;
+        self
+    }
+
+    /// Listens on an address (eg: "127.0.0.1:2525") and serves SMTP clients until an error occurs, see serve
+    pub fn listen<A: ToSocketAddrs>(self, address: A) -> std::io::Result<()> {
+        self.serve(TcpListener::bind(address)?)
+    }
+
+    /// Serves the SMTP clients connecting to the listener, each one on its own thread (! blocking)
+    pub fn serve(self, listener: TcpListener) -> std::io::Result<()> {
+        let bridge: Arc<SmtpBridge> = Arc::new(self);
+
+        for stream in listener.incoming() {
+            let stream: TcpStream = stream?;
+            let bridge: Arc<SmtpBridge> = bridge.clone();
+            std::thread::Builder::new()
+                .name("pushover-smtp".into())
+                .spawn(move || {
+                    // A broken connection only concerns its client
+                    let _ = bridge.session(stream);
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Forwards a raw e-mail (RFC 5322) to the recipients of its addresses.
+    ///
+    /// Returns the result of each call to the API, with the address it was made for.
+    /// Addresses forwarded to the same recipient are only sent once.
+    pub fn forward(&self, addresses: &[String], email: &[u8]) -> Vec<(String, Result<PushoverResponse, PushoverError>)> {
+        let email = match MessageParser::default().parse(email) {
+            Some(email) => email,
+            None => {
+                let error: PushoverError = PushoverError::InvalidInput("Cannot parse the e-mail".into());
+                return addresses.iter().map(|address| (address.clone(), Err(error.clone()))).collect();
+            },
+        };
+
+        let title: Option<String> = email.subject()
+            .map(str::trim)
+            .filter(|subject| !subject.is_empty())
+            .map(|subject| truncate(subject, PUSHOVER_API_MAX_TITLE_LENGTH));
+        let body: String = email.body_text(0).map(|body| body.trim().to_owned()).unwrap_or_default();
+        let text: String = match (body.is_empty(), &title) {
+            (false, _) => truncate(&body, PUSHOVER_API_MAX_MESSAGE_LENGTH),
+            (true, Some(title)) => title.clone(),
+            (true, None) => "(empty e-mail)".to_owned(),
+        };
+
+        // An image too large for the API is left out, the text is still forwarded
+        let attachment: Option<Result<PathBuf, PushoverError>> = email.attachments()
+            .find(|part| is_image(part) && part.contents().len() as u64 <= PUSHOVER_API_ATTACHMENT_MAX_SIZE_BYTES)
+            .map(save_attachment);
+
+        let mut targets: Vec<(String, Option<String>)> = Vec::new();
+        let mut results: Vec<(String, Result<PushoverResponse, PushoverError>)> = Vec::new();
+        for address in addresses {
+            let target: (String, Option<String>) = self.target_of(address);
+            if targets.contains(&target) {
+                continue;
+            }
+
+            let (user_key, device) = &target;
+            let result: Result<PushoverResponse, PushoverError> = match &attachment {
+                Some(Ok(path)) => {
+                    let mut builder: AttachmentMessageBuilder = AttachmentMessageBuilder::new(user_key, &self.app_token, &text)
+                        .set_attachment(path.to_string_lossy().into_owned());
+                    if let Some(title) = &title {
+                        builder = builder.set_title(title);
+                    }
+                    if let Some(device) = device {
+                        builder = builder.set_device(device);
+                    }
+                    builder.build()
+                        .map_err(|error| PushoverError::InvalidInput(error.to_string()))
+                        .and_then(|message| self.client.send_with_attachment(&message))
+                },
+                Some(Err(error)) => Err(error.clone()),
+                None => {
+                    let mut builder: MessageBuilder = MessageBuilder::new(user_key, &self.app_token, &text);
+                    if let Some(title) = &title {
+                        builder = builder.set_title(title);
+                    }
+                    if let Some(device) = device {
+                        builder = builder.set_device(device);
+                    }
+                    self.client.send(&builder.build())
+                },
+            };
+
+            targets.push(target);
+            results.push((address.clone(), result));
+        }
+
+        if let Some(Ok(path)) = attachment {
+            let _ = std::fs::remove_file(path);
+        }
+
+        results
+    }
+
+    /// The user key and device an address is forwarded to
+    fn target_of(&self, address: &str) -> (String, Option<String>) {
+        let local_part: String = address.rsplit_once('@').map_or(address, |(local_part, _)| local_part).to_lowercase();
+
+        match self.recipients.get(&local_part) {
+            Some(SmtpRecipient::Device(device)) => (self.user_key.clone(), Some(device.clone())),
+            Some(SmtpRecipient::UserKey(user_key)) => (user_key.clone(), None),
+            None => (self.user_key.clone(), None),
+        }
+    }
+
+    /// Speaks SMTP with a client until it quits
+    fn session(&self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+        let mut writer: TcpStream = stream.try_clone()?;
+        let mut reader: BufReader<TcpStream> = BufReader::new(stream);
+
+        let mut sender: Option<String> = None;
+        let mut addresses: Vec<String> = Vec::new();
+        let mut line: Vec<u8> = Vec::new();
+
+        reply(&mut writer, "220 pushover-rs SMTP bridge ready")?;
+
+        loop {
+            if read_line(&mut reader, &mut line, SMTP_MAX_COMMAND_LENGTH)? == 0 {
+                return Ok(());
+            }
+            let command: String = String::from_utf8_lossy(&line).trim_end().to_owned();
+            let (verb, argument) = command.split_once(' ').unwrap_or((&command, ""));
+
+            match verb.to_uppercase().as_str() {
+                "EHLO" => reply(&mut writer, &format!("250-pushover-rs\r\n250-SIZE {}\r\n250 8BITMIME", self.max_size))?,
+                "HELO" => reply(&mut writer, "250 pushover-rs")?,
+                "MAIL" => match path_of(argument, "FROM:") {
+                    Some(path) => {
+                        sender = Some(path);
+                        addresses.clear();
+                        reply(&mut writer, "250 OK")?;
+                    },
+                    None => reply(&mut writer, "501 Syntax: MAIL FROM:<address>")?,
+                },
+                "RCPT" => match (path_of(argument, "TO:"), &sender) {
+                    (_, None) => reply(&mut writer, "503 MAIL first")?,
+                    (None, _) => reply(&mut writer, "501 Syntax: RCPT TO:<address>")?,
+                    (Some(_), _) if addresses.len() >= SMTP_MAX_RECIPIENTS => reply(&mut writer, "452 Too many recipients")?,
+                    (Some(address), _) => {
+                        addresses.push(address);
+                        reply(&mut writer, "250 OK")?;
+                    },
+                },
+                "DATA" if addresses.is_empty() => reply(&mut writer, "503 RCPT first")?,
+                "DATA" => {
+                    reply(&mut writer, "354 End data with <CR><LF>.<CR><LF>")?;
+                    let email: Option<Vec<u8>> = self.read_data(&mut reader)?;
+
+                    match email {
+                        None => reply(&mut writer, "552 Message too large")?,
+                        Some(email) => reply(&mut writer, &data_reply(&self.forward(&addresses, &email)))?,
+                    }
+
+                    sender = None;
+                    addresses.clear();
+                },
+                "RSET" => {
+                    sender = None;
+                    addresses.clear();
+                    reply(&mut writer, "250 OK")?;
+                },
+                "NOOP" => reply(&mut writer, "250 OK")?,
+                "QUIT" => return reply(&mut writer, "221 Bye"),
+                _ => reply(&mut writer, "502 Command not implemented")?,
+            }
+        }
+    }
+
+    /// Reads the content of an e-mail, up to the line holding a single dot. None if it is too large.
+    fn read_data(&self, reader: &mut BufReader<TcpStream>) -> std::io::Result<Option<Vec<u8>>> {
+        let mut email: Vec<u8> = Vec::new();
+        let mut line: Vec<u8> = Vec::new();
+        let mut too_large: bool = false;
+
+        loop {
+            if read_line(reader, &mut line, self.max_size as u64 + 2)? == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            if line == b".\r\n" || line == b".\n" {
+                break;
+            }
+
+            let content: &[u8] = line.strip_prefix(b".").unwrap_or(&line);
+            too_large = too_large || email.len() + content.len() > self.max_size;
+            if !too_large {
+                email.extend_from_slice(content);
+            }
+        }
+
+        Ok(if too_large { None } else { Some(email) })
+    }
+}
+
+/// Reads a line, of at most limit bytes, into the buffer. Returns the number of bytes read (0 at the end of the stream).
+fn read_line(reader: &mut BufReader<TcpStream>, line: &mut Vec<u8>, limit: u64) -> std::io::Result<usize> {
+    line.clear();
+    reader.by_ref().take(limit).read_until(b'\n', line)
+}
+
+fn reply(writer: &mut TcpStream, reply: &str) -> std::io::Result<()> {
+    writer.write_all(format!("{}\r\n", reply).as_bytes())?;
+    writer.flush()
+}
+
+/// Extracts the address of a "FROM:<address>" or "TO:<address>" argument
+fn path_of(argument: &str, prefix: &str) -> Option<String> {
+    let argument: &str = argument.trim_start();
+    if !argument.get(..prefix.len())?.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+
+    let path: &str = argument[prefix.len()..].trim_start();
+    let path: &str = path.strip_prefix('<')?.split_once('>')?.0;
+    Some(path.to_owned())
+}
+
+/// The reply to the DATA of an e-mail, given the results of its forwarding
+pub(crate) fn data_reply(results: &[(String, Result<PushoverResponse, PushoverError>)]) -> String {
+    let forwarded: bool = results.iter().any(|(_, result)| result.is_ok());
+
+    // Once a recipient got the e-mail, a failure would only make the client send it to everyone again
+    match results.iter().find_map(|(_, result)| result.as_ref().err()) {
+        None => "250 OK: forwarded".to_owned(),
+        Some(error) if forwarded => format!("250 OK: partly forwarded, {}", single_line(error)),
+        Some(error) if error.is_temporary() => format!("451 Cannot forward: {}", single_line(error)),
+        Some(error) => format!("554 Cannot forward: {}", single_line(error)),
+    }
+}
+
+fn single_line(error: &PushoverError) -> String {
+    error.to_string().replace(['\r', '\n'], " ")
+}
+
+fn is_image(part: &MessagePart<'_>) -> bool {
+    part.content_type().is_some_and(|content_type| content_type.ctype().eq_ignore_ascii_case("image"))
+}
+
+/// Writes an attachment to a temporary file, named with an extension matching its type
+fn save_attachment(part: &MessagePart<'_>) -> Result<PathBuf, PushoverError> {
+    let contents: &[u8] = part.contents();
+
+    // The subtype comes from the e-mail: only letters and digits make it to the path
+    let extension: String = match part.content_type().and_then(|content_type| content_type.subtype()) {
+        Some(subtype) if subtype.eq_ignore_ascii_case("jpeg") => "jpg".to_owned(),
+        Some(subtype) => subtype.to_ascii_lowercase().chars().filter(|c| c.is_ascii_alphanumeric()).collect(),
+        None => String::new(),
+    };
+    let extension: &str = if extension.is_empty() { "bin" } else { &extension };
+    let path: PathBuf = std::env::temp_dir().join(format!(
+        "pushover-smtp-{}-{}.{}",
+        std::process::id(),
+        NEXT_ATTACHMENT_ID.fetch_add(1, Ordering::Relaxed),
+        extension,
+    ));
+
+    // The temporary directory is shared: never follow (or overwrite) a file someone else put there
+    std::fs::OpenOptions::new().write(true).create_new(true).open(&path)?.write_all(contents)?;
+    Ok(path)
+}
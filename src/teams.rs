@@ -0,0 +1,24 @@
+/*!
+Pushover for Teams API (https://pushover.net/api/teams)
+
+Every call is authenticated with a team token, which is distinct from your application token.
+These functions use a default `PushoverClient`, see its methods of the same name to use a configured client.
+ */
+
+pub use crate::pushover::data::{TeamMember, TeamResponse, TeamUser, TeamUserBuilder};
+use crate::{PushoverClient, PushoverError, PushoverResponse};
+
+/// Shows the team's name and members (non-blocking)
+pub async fn get_team(team_token: &str) -> Result<TeamResponse, PushoverError> {
+    PushoverClient::new().get_team(team_token).await
+}
+
+/// Adds a user to the team, creating their account if needed (non-blocking)
+pub async fn add_team_user(user: TeamUser) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().add_team_user(&user).await
+}
+
+/// Removes a user from the team, identified by their e-mail address (non-blocking)
+pub async fn remove_team_user(team_token: &str, email: &str) -> Result<PushoverResponse, PushoverError> {
+    PushoverClient::new().remove_team_user(team_token, email).await
+}
@@ -0,0 +1,572 @@
+/*!
+An in-process fake of the Pushover API, to test code sending notifications entirely offline. (Requires the `testing` feature)
+
+`MockPushover` is a transport: give a clone of it to `PushoverClient::set_transport` (or the blocking client's),
+then assert on the requests it recorded. Clones share the same state.
+
+The mock emulates the endpoints the clients call: `messages.json`, receipts (status and cancellation), `users/validate.json`,
+`apps/limits.json`, the teams and the licensing APIs, with the same JSON bodies, HTTP statuses and rate limit headers as Pushover.
+ */
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::transport::{Method, Transport, TransportFuture, TransportRequest, TransportResponse};
+
+/**
+ A fake Pushover API, recording every request it receives.
+
+ By default, any non-empty application token and user key is accepted. Once tokens or users are registered,
+ only those are accepted.
+ **/
+#[derive(Debug, Clone, Default)]
+pub struct MockPushover {
+    state: Arc<Mutex<MockState>>,
+}
+
+/**
+ A request received by the mock, with its fields decoded (from JSON, multipart or url-encoded bodies, and the query string).
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The API path, eg: "messages.json" or "receipts/abc/cancel.json"
+    pub path: String,
+    pub fields: BTreeMap<String, String>,
+    /// The file sent along with a multipart request, if any
+    pub attachment: Option<RecordedAttachment>,
+    /// The request exactly as the transport received it
+    pub raw: TransportRequest,
+}
+
+impl RecordedRequest {
+    /// The value of a field
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(|value| value.as_str())
+    }
+}
+
+/**
+ A file received in a multipart request.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/**
+ The state of an emergency-priority message's receipt.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockReceipt {
+    pub receipt: String,
+    pub user_key: String,
+    pub acknowledged: bool,
+    pub cancelled: bool,
+    pub expires_at: u64,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    app_tokens: Vec<String>,
+    users: HashMap<String, Vec<String>>,
+    limit: u32,
+    remaining: u32,
+    reset: u64,
+    license_credits: u32,
+    team_members: Vec<Value>,
+    receipts: Vec<MockReceipt>,
+    queued_responses: VecDeque<(u16, String)>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            app_tokens: Vec::new(),
+            users: HashMap::new(),
+            limit: 10000,
+            remaining: 10000,
+            reset: now() + 30 * 24 * 3600,
+            license_credits: 10,
+            team_members: Vec::new(),
+            receipts: Vec::new(),
+            queued_responses: VecDeque::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl MockPushover {
+    /// Creates a mock accepting any non-empty token and user key
+    pub fn new() -> Self {
+        MockPushover::default()
+    }
+
+    /// Registers a valid application token. Once one is registered, unknown tokens are refused.
+    pub fn add_app_token(self, app_token: &str) -> MockPushover {
+        self.state().app_tokens.push(app_token.to_owned());
+        self
+    }
+
+    /// Registers a valid user or group key, with its device names. Once one is registered, unknown keys are refused.
+    pub fn add_user(self, user_key: &str, devices: &[&str]) -> MockPushover {
+        self.state().users.insert(user_key.to_owned(), devices.iter().map(|device| device.to_string()).collect());
+        self
+    }
+
+    /// Sets the application's monthly message limit, the messages left and the reset Unix timestamp
+    pub fn set_limits(self, limit: u32, remaining: u32, reset: u64) -> MockPushover {
+        {
+            let mut state = self.state();
+            state.limit = limit;
+            state.remaining = remaining;
+            state.reset = reset;
+        }
+        self
+    }
+
+    /// Sets the license credits left on the account
+    pub fn set_license_credits(self, credits: u32) -> MockPushover {
+        self.state().license_credits = credits;
+        self
+    }
+
+    /// Answers the next request with this HTTP status and body, instead of emulating the API
+    pub fn respond_next_with(&self, status: u16, body: &str) {
+        self.state().queued_responses.push_back((status, body.to_owned()));
+    }
+
+    /// Marks a receipt as acknowledged by the user
+    pub fn acknowledge(&self, receipt: &str) {
+        if let Some(found) = self.state().receipts.iter_mut().find(|found| found.receipt == receipt) {
+            found.acknowledged = true;
+        }
+    }
+
+    /// Every request received so far, in order
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// The requests received so far on `messages.json`, in order
+    pub fn messages(&self) -> Vec<RecordedRequest> {
+        self.state().requests.iter().filter(|request| request.path == "messages.json").cloned().collect()
+    }
+
+    /// The receipts issued for emergency-priority messages
+    pub fn receipts(&self) -> Vec<MockReceipt> {
+        self.state().receipts.clone()
+    }
+
+    /// Forgets the recorded requests
+    pub fn clear_requests(&self) {
+        self.state().requests.clear();
+    }
+
+    /// Answers a request the way the Pushover API would
+    pub fn handle(&self, request: TransportRequest) -> TransportResponse {
+        let recorded: RecordedRequest = decode_request(request);
+        let mut state = self.state();
+        state.requests.push(recorded.clone());
+
+        if let Some((status, body)) = state.queued_responses.pop_front() {
+            return TransportResponse { status, headers: json_headers(), body: body.into_bytes() };
+        }
+
+        let (status, mut body): (u16, Value) = state.route(&recorded);
+        body["request"] = Value::String(request_id());
+
+        let mut headers: Vec<(String, String)> = json_headers();
+        if recorded.path == "messages.json" {
+            headers.push(("X-Limit-App-Limit".into(), state.limit.to_string()));
+            headers.push(("X-Limit-App-Remaining".into(), state.remaining.to_string()));
+            headers.push(("X-Limit-App-Reset".into(), state.reset.to_string()));
+        }
+
+        TransportResponse { status, headers, body: body.to_string().into_bytes() }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Transport for MockPushover {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(self.handle(request)) })
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl crate::blocking::Transport for MockPushover {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, crate::PushoverError> {
+        Ok(self.handle(request))
+    }
+}
+
+impl MockState {
+    fn route(&mut self, request: &RecordedRequest) -> (u16, Value) {
+        let path: &str = request.path.as_str();
+        let segments: Vec<&str> = path.trim_end_matches(".json").split('/').collect();
+
+        match (request.method, segments.as_slice()) {
+            (Method::Post, ["messages"]) => self.send_message(request),
+            (Method::Post, ["users", "validate"]) => self.validate_user(request),
+            (Method::Get, ["apps", "limits"]) => self.check_app_token(request).unwrap_or_else(|| {
+                (200, json!({ "status": 1, "limit": self.limit, "remaining": self.remaining, "reset": self.reset }))
+            }),
+            (Method::Post, ["receipts", receipt, "cancel"]) => self.cancel_receipt(request, receipt),
+            (Method::Get, ["receipts", receipt]) => self.receipt_status(request, receipt),
+            (Method::Get, ["teams"]) => self.show_team(request),
+            (Method::Post, ["teams", "add_user"]) => self.add_team_member(request),
+            (Method::Post, ["teams", "remove_user"]) => self.remove_team_member(request),
+            (Method::Get, ["licenses"]) => self.check_app_token(request).unwrap_or_else(|| {
+                (200, json!({ "status": 1, "credits": self.license_credits }))
+            }),
+            (Method::Post, ["licenses", "assign"]) => self.assign_license(request),
+            _ => (404, json!({ "status": 0, "errors": [format!("{} {} is not part of the API", request.method, path)] })),
+        }
+    }
+
+    /// Refuses unknown application tokens
+    fn check_app_token(&self, request: &RecordedRequest) -> Option<(u16, Value)> {
+        let token: &str = request.field("token").unwrap_or_default();
+        if token.is_empty() || (!self.app_tokens.is_empty() && !self.app_tokens.iter().any(|known| known == token)) {
+            return Some(refusal("token", "application token is invalid"));
+        }
+        None
+    }
+
+    /// Refuses unknown user keys, and unknown devices of known users
+    fn check_users(&self, request: &RecordedRequest) -> Option<(u16, Value)> {
+        let users: Vec<&str> = request.field("user").unwrap_or_default().split(',').map(str::trim).collect();
+        if users.iter().any(|user| user.is_empty() || (!self.users.is_empty() && !self.users.contains_key(*user))) {
+            return Some(refusal("user", "user identifier is not a valid user, group, or subscribed user key"));
+        }
+
+        let devices: Vec<&str> = request.field("device").unwrap_or_default().split(',').map(str::trim).filter(|device| !device.is_empty()).collect();
+        for user in users {
+            if let Some(known_devices) = self.users.get(user) {
+                if devices.iter().any(|device| !known_devices.iter().any(|known| known == device.trim_start_matches('-'))) {
+                    return Some(refusal("device", "device name is not valid for user"));
+                }
+            }
+        }
+        None
+    }
+
+    fn send_message(&mut self, request: &RecordedRequest) -> (u16, Value) {
+        if let Some(refused) = self.check_app_token(request).or_else(|| self.check_users(request)) {
+            return refused;
+        }
+
+        if request.field("message").unwrap_or_default().trim().is_empty() {
+            return refusal("message", "message cannot be blank");
+        }
+
+        if self.remaining == 0 {
+            return (429, json!({ "status": 0, "errors": ["application is over quota"] }));
+        }
+
+        let priority: i64 = request.field("priority").and_then(|priority| priority.parse().ok()).unwrap_or(0);
+        if !(-2..=2).contains(&priority) {
+            return refusal("priority", "priority is invalid");
+        }
+
+        let mut body: Value = json!({ "status": 1 });
+        if priority == 2 {
+            let retry: u64 = request.field("retry").and_then(|retry| retry.parse().ok()).unwrap_or(0);
+            let expire: u64 = request.field("expire").and_then(|expire| expire.parse().ok()).unwrap_or(0);
+            if retry < 30 {
+                return refusal("retry", "retry must be at least 30 seconds");
+            }
+            if expire == 0 || expire > 10800 {
+                return refusal("expire", "expire must be between 1 and 10800 seconds");
+            }
+
+            let receipt: String = receipt_id();
+            self.receipts.push(MockReceipt {
+                receipt: receipt.clone(),
+                user_key: request.field("user").unwrap_or_default().to_owned(),
+                acknowledged: false,
+                cancelled: false,
+                expires_at: now() + expire,
+                tags: request.field("tags").unwrap_or_default().split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from).collect(),
+            });
+            body["receipt"] = Value::String(receipt);
+        }
+
+        self.remaining -= 1;
+        (200, body)
+    }
+
+    fn validate_user(&self, request: &RecordedRequest) -> (u16, Value) {
+        if let Some(refused) = self.check_app_token(request).or_else(|| self.check_users(request)) {
+            return refused;
+        }
+
+        let devices: Vec<String> = self.users.get(request.field("user").unwrap_or_default()).cloned().unwrap_or_default();
+        (200, json!({ "status": 1, "group": 0, "devices": devices, "licenses": ["Android", "iOS", "Desktop"] }))
+    }
+
+    fn receipt_status(&self, request: &RecordedRequest, receipt: &str) -> (u16, Value) {
+        if let Some(refused) = self.check_app_token(request) {
+            return refused;
+        }
+
+        match self.receipts.iter().find(|found| found.receipt == receipt) {
+            Some(found) => {
+                let expired: bool = found.cancelled || found.expires_at <= now();
+                (200, json!({
+                    "status": 1,
+                    "acknowledged": found.acknowledged as u8,
+                    "acknowledged_at": if found.acknowledged { now() } else { 0 },
+                    "acknowledged_by": if found.acknowledged { found.user_key.as_str() } else { "" },
+                    "acknowledged_by_device": "",
+                    "last_delivered_at": now(),
+                    "expired": expired as u8,
+                    "expires_at": found.expires_at,
+                    "called_back": 0,
+                    "called_back_at": 0,
+                }))
+            },
+            None => refusal("receipt", "receipt not found; may be invalid or expired"),
+        }
+    }
+
+    fn cancel_receipt(&mut self, request: &RecordedRequest, receipt: &str) -> (u16, Value) {
+        if let Some(refused) = self.check_app_token(request) {
+            return refused;
+        }
+
+        match self.receipts.iter_mut().find(|found| found.receipt == receipt) {
+            Some(found) => {
+                found.cancelled = true;
+                (200, json!({ "status": 1 }))
+            },
+            None => refusal("receipt", "receipt not found; may be invalid or expired"),
+        }
+    }
+
+    fn show_team(&self, request: &RecordedRequest) -> (u16, Value) {
+        if request.field("token").unwrap_or_default().is_empty() {
+            return refusal("token", "team token is invalid");
+        }
+        (200, json!({ "status": 1, "name": "Mock Team", "users": self.team_members }))
+    }
+
+    fn add_team_member(&mut self, request: &RecordedRequest) -> (u16, Value) {
+        if request.field("token").unwrap_or_default().is_empty() {
+            return refusal("token", "team token is invalid");
+        }
+
+        let email: &str = request.field("email").unwrap_or_default();
+        if !email.contains('@') {
+            return refusal("email", "email is invalid");
+        }
+
+        self.team_members.retain(|member| member["email"] != email);
+        self.team_members.push(json!({
+            "name": request.field("name").unwrap_or(email),
+            "email": email,
+            "user": format!("u{}", &receipt_id()[1..]),
+            "admin": request.field("admin") == Some("true"),
+            "devices": [],
+            "groups": request.field("group").map(|group| vec![group]).unwrap_or_default(),
+        }));
+        (200, json!({ "status": 1 }))
+    }
+
+    fn remove_team_member(&mut self, request: &RecordedRequest) -> (u16, Value) {
+        if request.field("token").unwrap_or_default().is_empty() {
+            return refusal("token", "team token is invalid");
+        }
+
+        let email: &str = request.field("email").unwrap_or_default();
+        let before: usize = self.team_members.len();
+        self.team_members.retain(|member| member["email"] != email);
+        if self.team_members.len() == before {
+            return refusal("email", "email is not a member of this team");
+        }
+        (200, json!({ "status": 1 }))
+    }
+
+    fn assign_license(&mut self, request: &RecordedRequest) -> (u16, Value) {
+        if let Some(refused) = self.check_app_token(request) {
+            return refused;
+        }
+
+        if request.field("user").is_none() && request.field("email").is_none() {
+            return refusal("user", "user or email must be supplied");
+        }
+
+        if self.license_credits == 0 {
+            return (400, json!({ "status": 0, "errors": ["no license credits remaining"] }));
+        }
+
+        self.license_credits -= 1;
+        (200, json!({ "status": 1, "credits": self.license_credits }))
+    }
+}
+
+/// A refused request, with the faulty parameter flagged as Pushover does
+fn refusal(parameter: &str, error: &str) -> (u16, Value) {
+    let mut body: Value = json!({ "status": 0, "errors": [error] });
+    body[parameter] = Value::String("invalid".into());
+    (400, body)
+}
+
+fn json_headers() -> Vec<(String, String)> {
+    vec![("Content-Type".into(), "application/json; charset=utf-8".into())]
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+/// A unique, GUID-looking identifier
+fn request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count: u64 = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos()).unwrap_or_default();
+    let hex: String = format!("{:016x}{:016x}", nanos as u64, count);
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// A unique, receipt-looking identifier (30 alphanumeric characters)
+fn receipt_id() -> String {
+    format!("r{}", request_id().replace('-', "")).chars().take(30).collect()
+}
+
+fn decode_request(request: TransportRequest) -> RecordedRequest {
+    let (url, query): (&str, &str) = request.url.split_once('?').unwrap_or((request.url.as_str(), ""));
+    let path: String = url.split_once("/1/").map(|(_, path)| path).unwrap_or(url).to_owned();
+
+    let mut fields: BTreeMap<String, String> = decode_urlencoded(query.as_bytes());
+    let mut attachment: Option<RecordedAttachment> = None;
+    let content_type: String = request.header("Content-Type").unwrap_or_default().to_lowercase();
+
+    if content_type.starts_with("application/json") {
+        if let Ok(Value::Object(object)) = serde_json::from_slice::<Value>(&request.body) {
+            for (name, value) in object {
+                let value: String = match value {
+                    Value::String(text) => text,
+                    Value::Null => continue,
+                    other => other.to_string(),
+                };
+                fields.insert(name, value);
+            }
+        }
+    } else if content_type.starts_with("multipart/form-data") {
+        let boundary: &str = request.header("Content-Type").unwrap_or_default().split("boundary=").nth(1).unwrap_or_default();
+        let (multipart_fields, file) = decode_multipart(&request.body, boundary);
+        fields.extend(multipart_fields);
+        attachment = file;
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        fields.extend(decode_urlencoded(&request.body));
+    }
+
+    RecordedRequest { method: request.method, path, fields, attachment, raw: request }
+}
+
+fn decode_multipart(body: &[u8], boundary: &str) -> (BTreeMap<String, String>, Option<RecordedAttachment>) {
+    let mut fields: BTreeMap<String, String> = BTreeMap::new();
+    let mut attachment: Option<RecordedAttachment> = None;
+    let delimiter: Vec<u8> = format!("--{}", boundary).into_bytes();
+
+    for part in split_bytes(body, &delimiter) {
+        let part: &[u8] = part.strip_prefix(b"\r\n").unwrap_or(part);
+        let part: &[u8] = part.strip_suffix(b"\r\n").unwrap_or(part);
+        let Some(header_end) = find_bytes(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers: String = String::from_utf8_lossy(&part[..header_end]).into_owned();
+        let content: &[u8] = &part[header_end + 4..];
+
+        let name: Option<String> = header_parameter(&headers, "name");
+        let file_name: Option<String> = header_parameter(&headers, "filename");
+        let part_type: String = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Type: ").or_else(|| line.strip_prefix("content-type: ")))
+            .unwrap_or("application/octet-stream")
+            .to_owned();
+
+        match (name, file_name) {
+            (_, Some(file_name)) => attachment = Some(RecordedAttachment { file_name, content_type: part_type, bytes: content.to_vec() }),
+            (Some(name), None) => {
+                fields.insert(name, String::from_utf8_lossy(content).into_owned());
+            },
+            _ => {},
+        }
+    }
+
+    (fields, attachment)
+}
+
+/// The value of a `name="value"` parameter of a Content-Disposition header
+fn header_parameter(headers: &str, parameter: &str) -> Option<String> {
+    let needle: String = format!(" {}=\"", parameter);
+    let start: usize = headers.find(&needle)? + needle.len();
+    let end: usize = headers[start..].find('"')? + start;
+    Some(headers[start..end].to_owned())
+}
+
+fn split_bytes<'a>(haystack: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut parts: Vec<&[u8]> = Vec::new();
+    let mut rest: &[u8] = haystack;
+    while let Some(position) = find_bytes(rest, delimiter) {
+        parts.push(&rest[..position]);
+        rest = &rest[position + delimiter.len()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn decode_urlencoded(input: &[u8]) -> BTreeMap<String, String> {
+    String::from_utf8_lossy(input)
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes: &[u8] = input.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index: usize = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex: Option<u8> = std::str::from_utf8(&bytes[index + 1..index + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    },
+                    None => decoded.push(b'%'),
+                }
+            },
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
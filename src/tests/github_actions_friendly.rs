@@ -1,4 +1,14 @@
-use crate::{AttachmentMessageBuilder, Message, MessageBuilder, PushoverSound};
+use crate::{AttachmentMessage, AttachmentMessageBuilder, Message, MessageBuilder, Priority, PushoverSound};
+use crate::{Deduplication, DeduplicationStats, MessageTemplate, PreparedBody, PreparedRequest, PushoverClient, PushoverError, PushoverResponse, Recipients};
+use std::collections::HashMap;
+use crate::licensing::{LicenseCreditsResponse, LicenseOs};
+use crate::pushover::deduplicator::Deduplicator;
+use crate::pushover::pacer::Pacer;
+use crate::pushover::runtime;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use crate::transport::{Method, MultipartFile, Transport, TransportFuture, TransportRequest, TransportResponse};
+use crate::teams::{TeamResponse, TeamUser, TeamUserBuilder};
 
 #[test]
 pub fn test_message_builder() {
@@ -15,9 +25,9 @@ pub fn test_message_builder() {
         .set_title("Title")
         .set_url("https://www.google.be/", Some("Google"))
         .modify_message("test message")
-        .set_priority(100) // "Error": Out of the [-2, 2] boundary -> Should be reset to 0
+        .set_priority(Priority::Normal)
         .set_sound(PushoverSound::CASHREGISTER)
-        .set_timestamp(1635861224)
+        .set_timestamp(UNIX_EPOCH + Duration::from_secs(1635861224))
         .set_device("device a")
         .set_device("device b")  // should overwrite existing device
         .build();
@@ -68,3 +78,1505 @@ fn test_build_attachment_too_large() {
 
     assert_eq!(message.is_err(), true);
 }
+
+#[test]
+fn test_team_user_builder() {
+    let user: TeamUser = TeamUserBuilder::new("team", "someone@example.com")
+        .set_name("Someone")
+        .set_admin(true)
+        .set_group("ops")
+        .remove_group()
+        .build();
+
+    assert_eq!(user.team_token, "team".to_owned());
+    assert_eq!(user.email, "someone@example.com".to_owned());
+    assert_eq!(user.name, Some("Someone".to_owned()));
+    assert_eq!(user.admin, Some(true));
+    assert_eq!(user.instant, None);
+    assert_eq!(user.group, None);
+}
+
+#[test]
+fn test_team_response_deserialization() {
+    let json: &str = r#"{
+        "name": "Example Team",
+        "users": [
+            {"name": "Admin", "email": "admin@example.com", "user": "uQiRzpo4DXghDmr9QzzfQu27cmVRsG", "admin": true, "devices": ["iphone"], "groups": ["ops"]},
+            {"name": "Member", "email": "member@example.com", "user": "u2nMLNBHr4jcj4Zv3GnxWCTGVdQsf9", "admin": false}
+        ],
+        "status": 1,
+        "request": "7dbbb4fe-4bc7-4a1e-a94d-0a6a5c3ba5d2"
+    }"#;
+    let response: TeamResponse = serde_json::from_str(json).unwrap();
+
+    assert_eq!(response.status, 1);
+    assert_eq!(response.name, Some("Example Team".to_owned()));
+    assert_eq!(response.users.len(), 2);
+    assert!(response.users[0].admin);
+    assert_eq!(response.users[0].groups, vec!["ops".to_owned()]);
+    assert_eq!(response.users[1].user_key, "u2nMLNBHr4jcj4Zv3GnxWCTGVdQsf9".to_owned());
+    assert!(response.users[1].devices.is_empty());
+}
+
+#[test]
+fn test_team_response_error_mapping() {
+    let json: &str = r#"{"token": "invalid", "errors": ["team token is invalid"], "status": 0, "request": "3c0b7d0f-1a4e-4b8e-9f0e-2d7a1c5e6b90"}"#;
+    let response: TeamResponse = serde_json::from_str(json).unwrap();
+
+    match response.checked() {
+        Err(PushoverError::InvalidToken { errors, .. }) => assert_eq!(errors, vec!["team token is invalid".to_owned()]),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_license_os_names() {
+    assert_eq!(LicenseOs::Android.to_string(), "Android".to_owned());
+    assert_eq!(LicenseOs::Ios.to_string(), "iOS".to_owned());
+    assert_eq!(LicenseOs::Desktop.to_string(), "Desktop".to_owned());
+}
+
+#[test]
+fn test_license_credits_error_mapping() {
+    let json: &str = r#"{"token": "invalid", "errors": ["application token is invalid"], "status": 0, "request": "5042853c-402d-4a18-abcb-168734a801de"}"#;
+    let response: LicenseCreditsResponse = serde_json::from_str(json).unwrap();
+    let error: PushoverError = PushoverError::from_api_response(response.request, response.token.as_deref(), None, response.errors);
+
+    match error {
+        PushoverError::InvalidToken { errors, .. } => assert_eq!(errors, vec!["application token is invalid".to_owned()]),
+        other => panic!("Unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn test_refusal_as_response() {
+    let json: &str = r#"{"user": "invalid", "errors": ["user identifier is invalid"], "status": 0, "request": "5042853c-402d-4a18-abcb-168734a801de"}"#;
+    let refused: PushoverResponse = serde_json::from_str(json).unwrap();
+    let response: PushoverResponse = PushoverResponse::from_refusal(PushoverError::from(refused)).unwrap();
+
+    assert_eq!(response.status, 0);
+    assert_eq!(response.request, "5042853c-402d-4a18-abcb-168734a801de".to_owned());
+    assert_eq!((response.user, response.token), (Some("invalid".to_owned()), None));
+    assert_eq!(response.errors, Some(vec!["user identifier is invalid".to_owned()]));
+    assert!(PushoverResponse::from_refusal(PushoverError::InvalidInput("test".into())).is_err());
+}
+
+#[test]
+fn test_recipients_batches() {
+    let keys: Vec<String> = (0..120).map(|i| format!("key{i}")).collect();
+    let recipients: Recipients = keys.iter()
+        .collect::<Recipients>()
+        .add_key("key0") // duplicate -> ignored
+        .add_key("  ") // empty -> ignored
+        .set_individual_fallback(true);
+
+    assert_eq!(recipients.len(), 120);
+    assert!(recipients.has_individual_fallback());
+
+    let batch_sizes: Vec<usize> = recipients.batches().map(|batch| batch.len()).collect();
+    assert_eq!(batch_sizes, vec![50, 50, 20]);
+    assert_eq!(recipients.batches().next().unwrap().join(",").split(',').count(), 50);
+}
+
+#[tokio::test]
+async fn test_client_send_all_nothing() {
+    let client: PushoverClient = PushoverClient::new()
+        .set_concurrency(0) // Should be raised to 1
+        .set_requests_per_second(10);
+    let responses = client.send_all(Vec::new()).await;
+    assert!(responses.is_empty());
+}
+
+#[test]
+#[cfg(feature = "blocking")]
+fn test_blocking_client_send_all_nothing() {
+    let client: crate::blocking::PushoverClient = crate::blocking::PushoverClient::new()
+        .set_concurrency(4)
+        .set_requests_per_second(10);
+    let responses = client.send_all(Vec::new());
+    assert!(responses.is_empty());
+}
+
+#[tokio::test]
+async fn test_pacing_spaces_out_requests() {
+    let pacer: Pacer = Pacer::new(Duration::from_millis(50));
+    let start: Instant = Instant::now();
+    for _ in 0..3 {
+        runtime::sleep_until(pacer.book_slot()).await;
+    }
+    // First slot is immediate, the two next ones are 50ms apart
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn test_runtime_read_file() {
+    let bytes: Vec<u8> = runtime::read_file("Cargo.toml".into()).await.unwrap();
+    assert!(bytes.starts_with(b"[package]"));
+
+    let missing = runtime::read_file("./testdata/doesnt_exist.bin".into()).await;
+    assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+}
+
+/// Answers every request with the same body, and keeps the requests
+#[derive(Clone, Default)]
+struct FakeTransport {
+    body: &'static str,
+    requests: Arc<Mutex<Vec<TransportRequest>>>,
+}
+
+impl FakeTransport {
+    /// Accepts every request
+    fn ok() -> Self {
+        FakeTransport {
+            body: r#"{"status":1,"request":"647d2300-702c-4b38-8b2f-d56326ae460b"}"#,
+            ..Default::default()
+        }
+    }
+
+    fn answer(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError> {
+        self.requests.lock().unwrap().push(request);
+        Ok(TransportResponse { status: 200, headers: Vec::new(), body: self.body.as_bytes().to_vec() })
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move { self.answer(request) })
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl crate::blocking::Transport for FakeTransport {
+    fn send(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError> {
+        self.answer(request)
+    }
+}
+
+#[tokio::test]
+async fn test_client_custom_transport() {
+    let transport: FakeTransport = FakeTransport::ok();
+    let client: PushoverClient = PushoverClient::new().set_transport(transport.clone());
+    let message: Message = MessageBuilder::new("abc", "def", "test message").set_title("Title").build();
+
+    let response = client.send(&message).await.unwrap();
+    assert_eq!(response.request, "647d2300-702c-4b38-8b2f-d56326ae460b".to_owned());
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, Method::Post);
+    assert_eq!(requests[0].url, "https://api.pushover.net/1/messages.json".to_owned());
+    assert_eq!(requests[0].header("content-type"), Some("application/json"));
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["token"], "def");
+    assert_eq!(body["user"], "abc");
+    assert_eq!(body["title"], "Title");
+}
+
+#[tokio::test]
+async fn test_client_refused_request() {
+    let transport: FakeTransport = FakeTransport {
+        body: r#"{"user":"invalid","errors":["user identifier is invalid"],"status":0,"request":"5042853c-402d-4a18-abcb-168734a801de"}"#,
+        ..Default::default()
+    };
+    let client: PushoverClient = PushoverClient::new().set_transport(transport);
+    let message: Message = MessageBuilder::new("abc", "def", "test message").build();
+
+    match client.send(&message).await {
+        Err(PushoverError::InvalidUser { errors, .. }) => assert_eq!(errors, vec!["user identifier is invalid".to_owned()]),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client_custom_transport() {
+    let transport: FakeTransport = FakeTransport::ok();
+    let client: crate::blocking::PushoverClient = crate::blocking::PushoverClient::new().set_transport(transport.clone());
+    let messages: Vec<Message> = (0..5)
+        .map(|i| MessageBuilder::new("abc", "def", format!("message {i}").as_str()).build())
+        .collect();
+
+    let responses = client.send_all(messages);
+    assert_eq!(responses.len(), 5);
+    assert!(responses.iter().all(|response| response.is_ok()));
+    assert_eq!(transport.requests.lock().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_client_validation_limits_receipt() {
+    let transport: FakeTransport = FakeTransport {
+        body: r#"{"status":1,"request":"r","group":0,"devices":["phone"],"licenses":["iOS"],"limit":10000,"remaining":7496,"reset":1393653600,"acknowledged":1,"acknowledged_by":"abc","expires_at":1393653600}"#,
+        ..Default::default()
+    };
+    let client: PushoverClient = PushoverClient::new().set_transport(transport.clone()).set_dry_run(true);
+
+    let validation = client.validate_user("def", "abc", Some("phone")).await.unwrap();
+    assert_eq!(validation.devices, vec!["phone".to_owned()]);
+    let limits = client.get_limits("def").await.unwrap();
+    assert_eq!((limits.limit, limits.remaining, limits.reset), (Some(10000), Some(7496), Some(1393653600)));
+    let receipt = client.get_receipt("def", "rcpt").await.unwrap();
+    assert!(receipt.is_acknowledged());
+    assert_eq!(receipt.acknowledged_by, "abc");
+
+    // Reading data is not prevented by the dry-run mode
+    assert!(client.take_dry_run_requests().is_empty());
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests[0].url, "https://api.pushover.net/1/users/validate.json");
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!((&body["token"], &body["user"], &body["device"]), (&"def".into(), &"abc".into(), &"phone".into()));
+    assert_eq!((requests[1].method, requests[1].url.as_str()), (Method::Get, "https://api.pushover.net/1/apps/limits.json?token=def"));
+    assert_eq!(requests[2].url, "https://api.pushover.net/1/receipts/rcpt.json?token=def");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client_validation_limits_receipt() {
+    let refused: FakeTransport = FakeTransport {
+        body: r#"{"user":"invalid","errors":["user key is invalid"],"status":0,"request":"r"}"#,
+        ..Default::default()
+    };
+    let client: crate::blocking::PushoverClient = crate::blocking::PushoverClient::new().set_transport(refused);
+    assert!(matches!(client.validate_user("def", "abc", None), Err(PushoverError::InvalidUser { .. })));
+    assert!(matches!(client.get_limits("def"), Err(PushoverError::Api { .. })));
+    assert!(matches!(client.get_receipt("def", "rcpt"), Err(PushoverError::Api { .. })));
+}
+
+#[test]
+fn test_transport_request_encoding() {
+    let get: TransportRequest = TransportRequest::get("https://api.pushover.net/1/teams.json", &[("token", "a b&c")]);
+    assert_eq!(get.method, Method::Get);
+    assert_eq!(get.url, "https://api.pushover.net/1/teams.json?token=a%20b%26c".to_owned());
+
+    let file: MultipartFile = MultipartFile {
+        field_name: "attachment".into(),
+        file_name: "image.jpg".into(),
+        content_type: "image/jpeg".into(),
+        bytes: vec![0xFF, 0xD8, 0xFF],
+    };
+    let multipart: TransportRequest = TransportRequest::multipart("https://api.pushover.net/1/messages.json", &[("token", "def".to_owned())], Some(file));
+    let content_type: &str = multipart.header("Content-Type").unwrap();
+    let boundary: &str = content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+    let body: String = String::from_utf8_lossy(&multipart.body).into_owned();
+
+    assert!(body.starts_with(&format!("--{boundary}\r\nContent-Disposition: form-data; name=\"token\"\r\n\r\ndef\r\n")));
+    assert!(body.contains("name=\"attachment\"; filename=\"image.jpg\"\r\nContent-Type: image/jpeg\r\n\r\n"));
+    assert!(body.ends_with(&format!("\r\n--{boundary}--\r\n")));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_mock_pushover_messages() {
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new()
+        .add_app_token("def")
+        .add_user("abc", &["phone"]);
+    let client: PushoverClient = PushoverClient::new().set_transport(mock.clone());
+
+    let message: Message = MessageBuilder::new("abc", "def", "test message")
+        .set_device("phone")
+        .set_priority(Priority::emergency())
+        .build();
+    let response = client.send(&message).await.unwrap();
+    assert_eq!(mock.receipts().len(), 1);
+    assert_eq!(response.status, 1);
+
+    let unknown_user: Message = MessageBuilder::new("xyz", "def", "test message").build();
+    assert!(matches!(client.send(&unknown_user).await, Err(PushoverError::InvalidUser { .. })));
+
+    let unknown_device: Message = MessageBuilder::new("abc", "def", "test message").set_device("tablet").build();
+    assert!(matches!(client.send(&unknown_device).await, Err(PushoverError::Api { .. })));
+
+    mock.respond_next_with(500, "<html>Internal Server Error</html>");
+    assert!(matches!(client.send(&message).await, Err(PushoverError::InvalidResponse(_))));
+
+    let messages = mock.messages();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0].field("message"), Some("test message"));
+    assert_eq!(messages[0].field("priority"), Some("2"));
+    assert_eq!(messages[0].field("retry"), Some("30"));
+    assert_eq!(messages[1].field("user"), Some("xyz"));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_mock_pushover_validation_limits_receipts() {
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new()
+        .add_app_token("def")
+        .add_user("abc", &["phone", "tablet"])
+        .set_limits(500, 2, 1_700_000_000);
+    let client: PushoverClient = PushoverClient::new().set_transport(mock.clone());
+
+    /* users/validate.json */
+    let validation = client.validate_user("def", "abc", Some("phone")).await.unwrap();
+    assert_eq!(validation.devices, vec!["phone".to_owned(), "tablet".to_owned()]);
+    assert!(matches!(client.validate_user("def", "xyz", None).await, Err(PushoverError::InvalidUser { .. })));
+    assert!(matches!(client.validate_user("def", "abc", Some("watch")).await, Err(PushoverError::Api { .. })));
+    assert!(matches!(client.validate_user("bad", "abc", None).await, Err(PushoverError::InvalidToken { .. })));
+
+    /* apps/limits.json, the remaining count following the messages sent */
+    let emergency: Message = MessageBuilder::new("abc", "def", "test message").set_priority(Priority::emergency()).build();
+    let receipt: String = client.send(&emergency).await.unwrap().receipt.unwrap();
+    let limits = client.get_limits("def").await.unwrap();
+    assert_eq!((limits.limit, limits.remaining, limits.reset), (Some(500), Some(1), Some(1_700_000_000)));
+
+    /* receipts/{receipt}.json */
+    let status = client.get_receipt("def", &receipt).await.unwrap();
+    assert!(!status.is_acknowledged());
+    assert_eq!(status.expired, 0);
+    mock.acknowledge(&receipt);
+    let status = client.get_receipt("def", &receipt).await.unwrap();
+    assert!(status.is_acknowledged());
+    assert_eq!(status.acknowledged_by, "abc");
+    client.cancel_receipt("def", &receipt).await.unwrap();
+    assert_eq!(client.get_receipt("def", &receipt).await.unwrap().expired, 1);
+    assert!(matches!(client.get_receipt("def", "unknown").await, Err(PushoverError::Api { .. })));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_recipients_individual_fallback() {
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new()
+        .add_app_token("def")
+        .add_user("abc", &[])
+        .add_user("ghi", &[]);
+    let client: PushoverClient = PushoverClient::new().set_transport(mock.clone());
+    let recipients: Recipients = Recipients::new()
+        .add_keys(["abc", "bad", "ghi"])
+        .set_individual_fallback(true);
+
+    // A bad user key -> The batch is sent again once per recipient
+    let results = client.send_to_recipients(&MessageBuilder::new("", "def", "test").build(), &recipients).await;
+    assert_eq!(mock.messages().len(), 4);
+    assert!(results["abc"].is_ok() && results["ghi"].is_ok());
+    assert!(matches!(results["bad"], Err(PushoverError::InvalidUser { .. })));
+
+    // A bad application token -> No fallback, it would be refused for every recipient
+    mock.clear_requests();
+    let results = client.send_to_recipients(&MessageBuilder::new("", "bad", "test").build(), &recipients).await;
+    assert_eq!(mock.messages().len(), 1);
+    assert!(results.values().all(|result| matches!(result, Err(PushoverError::InvalidToken { .. }))));
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_mock_pushover_attachment() {
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new();
+    let client: PushoverClient = PushoverClient::new().set_transport(mock.clone());
+    let message: crate::AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .set_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    client.send_with_attachment(&message).await.unwrap();
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].field("token"), Some("def"));
+    assert_eq!(requests[0].field("ttl"), Some("60"));
+    let attachment = requests[0].attachment.as_ref().unwrap();
+    assert_eq!(attachment.file_name, "Cargo.toml".to_owned());
+    assert_eq!(attachment.bytes, std::fs::read("Cargo.toml").unwrap());
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_mock_pushover_other_endpoints() {
+    use crate::licensing::LicenseAssignee;
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new().set_limits(10000, 0, 1393653600).set_license_credits(1);
+    let client: PushoverClient = PushoverClient::new().set_transport(mock.clone());
+
+    /* Over quota */
+    let over_quota = client.send(&MessageBuilder::new("abc", "def", "test").build()).await;
+    assert!(matches!(over_quota, Err(PushoverError::Api { http_status: Some(429), .. })));
+    assert!(over_quota.unwrap_err().is_temporary());
+
+    /* Teams */
+    let user: TeamUser = TeamUserBuilder::new("team", "someone@example.com").set_admin(true).build();
+    client.add_team_user(&user).await.unwrap();
+    let team: TeamResponse = client.get_team("team").await.unwrap();
+    assert_eq!(team.name, Some("Mock Team".to_owned()));
+    assert_eq!(team.users.len(), 1);
+    assert!(team.users[0].admin);
+    client.remove_team_user("team", "someone@example.com").await.unwrap();
+    assert!(client.remove_team_user("team", "someone@example.com").await.is_err());
+    assert!(matches!(client.get_team("").await, Err(PushoverError::InvalidToken { .. })));
+
+    /* Licensing */
+    assert_eq!(client.get_license_credits("def").await.unwrap(), 1);
+    client.assign_license("def", LicenseAssignee::Email("someone@example.com".into()), Some(LicenseOs::Ios)).await.unwrap();
+    assert_eq!(client.get_license_credits("def").await.unwrap(), 0);
+    assert!(client.assign_license("def", LicenseAssignee::User("abc".into()), None).await.is_err());
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 10);
+    assert_eq!(requests[7].path, "licenses/assign.json".to_owned());
+    assert_eq!(requests[7].field("os"), Some("iOS"));
+}
+
+#[tokio::test]
+async fn test_client_dry_run() {
+    let transport: FakeTransport = FakeTransport::default();
+    let client: PushoverClient = PushoverClient::new()
+        .set_transport(transport.clone())
+        .set_dry_run(true);
+    let message: Message = MessageBuilder::new("abc", "def", "test message")
+        .set_title("Title")
+        .set_ttl(Duration::from_secs(60))
+        .build();
+
+    let response = client.send(&message).await.unwrap();
+    assert_eq!(response.request, "dry-run".to_owned());
+    assert!(transport.requests.lock().unwrap().is_empty());
+
+    let prepared: Vec<PreparedRequest> = client.take_dry_run_requests();
+    assert_eq!(prepared.len(), 1);
+    assert!(client.take_dry_run_requests().is_empty());
+    assert_eq!(prepared[0].to_string(), r#"POST https://api.pushover.net/1/messages.json
+Content-Type: application/json
+
+{
+  "message": "test message",
+  "title": "Title",
+  "token": "def",
+  "ttl": 60,
+  "user": "abc"
+}
+"#);
+}
+
+#[test]
+fn test_prepared_attachment_request() {
+    let message: crate::AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .build()
+        .unwrap();
+    let prepared: PreparedRequest = PreparedRequest::from_attachment_message(&message).unwrap();
+
+    match prepared.body {
+        PreparedBody::Multipart { fields, attachment } => {
+            assert!(fields.contains(&("message".to_owned(), "test message".to_owned())));
+            let attachment = attachment.unwrap();
+            assert_eq!(attachment.content_type, "application/octet-stream".to_owned());
+            assert_eq!(attachment.size_bytes, std::fs::metadata("Cargo.toml").unwrap().len());
+        },
+        other => panic!("Unexpected body: {:?}", other),
+    }
+}
+
+#[test]
+fn test_message_template_rendering() {
+    #[derive(serde::Serialize)]
+    struct Check {
+        host: &'static str,
+        check: &'static str,
+        load: f32,
+        labels: HashMap<&'static str, &'static str>,
+    }
+
+    let template: MessageTemplate = MessageTemplate::new("{check} failed on {host}: load is {load} {{avg}}")
+        .set_title("[{labels.env}] {host} is down")
+        .set_url("https://monitoring.example.com/hosts/{host}", Some("{host} dashboard"))
+        .set_priority(Priority::High)
+        .set_sound(PushoverSound::SIREN);
+    let variables: Check = Check {
+        host: "db-1",
+        check: "load",
+        load: 12.5,
+        labels: HashMap::from([("env", "prod")]),
+    };
+    let message: Message = template.render("abc", "def", &variables).unwrap().build();
+
+    assert_eq!(message.message, "load failed on db-1: load is 12.5 {avg}".to_owned());
+    assert_eq!(message.title, Some("[prod] db-1 is down".to_owned()));
+    assert_eq!(message.url, Some("https://monitoring.example.com/hosts/db-1".to_owned()));
+    assert_eq!(message.url_title, Some("db-1 dashboard".to_owned()));
+    assert_eq!(message.priority, Some(1));
+    assert_eq!(message.sound, Some("siren".to_owned()));
+
+    let missing = template.render("abc", "def", &HashMap::from([("host", "db-1")]));
+    match missing {
+        Err(PushoverError::Template(reason)) => assert_eq!(reason, "Missing template variable: check".to_owned()),
+        other => panic!("Unexpected result: {:?}", other.map(|builder| builder.build())),
+    }
+
+    let unclosed = MessageTemplate::new("{host").render("abc", "def", &HashMap::from([("host", "db-1")]));
+    assert!(matches!(unclosed, Err(PushoverError::Template(_))));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_message_template_from_toml() {
+    let template: MessageTemplate = MessageTemplate::from_toml_str(r#"
+        title = "{host} is down"
+        message = "Check failed on {host}"
+        priority = 1
+        sound = "siren"
+    "#).unwrap();
+    assert_eq!(template.title, Some("{host} is down".to_owned()));
+    assert_eq!(template.priority, Some(Priority::High));
+    assert!(MessageTemplate::from_toml_str("title = 1").is_err());
+    // An invalid priority is refused when loading the template, rather than when rendering it
+    assert!(MessageTemplate::from_toml_str("message = \"a\"\npriority = 3").is_err());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_message_template_from_yaml() {
+    let template: MessageTemplate = MessageTemplate::from_yaml_str("title: \"{host} is down\"\nmessage: Check failed on {host}\nsound: siren\n").unwrap();
+    assert_eq!(template.message, "Check failed on {host}".to_owned());
+    assert_eq!(template.sound, Some("siren".to_owned()));
+}
+
+#[tokio::test]
+async fn test_client_deduplication() {
+    let client: PushoverClient = PushoverClient::new()
+        .set_dry_run(true)
+        .set_deduplication(Deduplication::new(Duration::from_secs(60)).set_summary(true));
+    let alert: Message = MessageBuilder::new("abc", "def", "disk full").set_title("db-1").build();
+    let other_recipient: Message = MessageBuilder::new("ghi", "def", "disk full").set_title("db-1").build();
+
+    assert!(client.send(&alert).await.is_ok());
+    assert!(matches!(client.send(&alert).await, Err(PushoverError::Suppressed { .. })));
+    assert!(matches!(client.clone().send(&alert).await, Err(PushoverError::Suppressed { .. })));
+    assert!(client.send(&other_recipient).await.is_ok());
+
+    assert_eq!(client.take_dry_run_requests().len(), 2);
+    assert_eq!(client.deduplication_stats(), DeduplicationStats { forwarded: 2, suppressed: 2, summaries: 0 });
+}
+
+#[test]
+fn test_deduplicator_windows() {
+    let deduplicator: Deduplicator = Deduplicator::new(Deduplication::new(Duration::from_secs(60)).set_summary(true));
+    let alert: Message = MessageBuilder::new("abc", "def", "disk full").set_title("db-1").build();
+    let start: Instant = Instant::now();
+
+    let (_, window_key) = deduplicator.check_at(&alert, start).unwrap();
+    deduplicator.sent_at(window_key, start);
+    assert!(deduplicator.check_at(&alert, start + Duration::from_secs(10)).is_err());
+    assert!(deduplicator.check_at(&alert, start + Duration::from_secs(20)).is_err());
+
+    // The window closed -> Sent, with the count of suppressed duplicates
+    let (summarized, _) = deduplicator.check_at(&alert, start + Duration::from_secs(90)).unwrap();
+    assert_eq!(summarized.message, "disk full\n\n(2 similar alerts suppressed)".to_owned());
+    // Until it is sent, the window stays closed and the count is kept for the next try
+    let (retried, window_key) = deduplicator.check_at(&alert, start + Duration::from_secs(95)).unwrap();
+    assert_eq!(retried.message, summarized.message);
+    deduplicator.sent_at(window_key, start + Duration::from_secs(95));
+
+    // Suppressed counts are forgotten once the grace period after their window is over
+    assert!(deduplicator.check_at(&alert, start + Duration::from_secs(100)).is_err());
+    let (late, _) = deduplicator.check_at(&alert, start + Duration::from_secs(95 + 60 + 3600)).unwrap();
+    assert_eq!(late.message, "disk full".to_owned());
+    assert_eq!(deduplicator.stats(), DeduplicationStats { forwarded: 2, suppressed: 3, summaries: 1 });
+}
+
+#[tokio::test]
+async fn test_client_deduplication_failed_send() {
+    /// Fails the first request, answers the others
+    #[derive(Clone, Default)]
+    struct FlakyTransport {
+        inner: FakeTransport,
+    }
+
+    impl Transport for FlakyTransport {
+        fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+            Box::pin(async move {
+                if self.inner.requests.lock().unwrap().is_empty() {
+                    self.inner.requests.lock().unwrap().push(request);
+                    return Err(PushoverError::transport("connection reset"));
+                }
+                self.inner.answer(request)
+            })
+        }
+    }
+
+    let transport: FlakyTransport = FlakyTransport { inner: FakeTransport::ok() };
+    let client: PushoverClient = PushoverClient::new()
+        .set_transport(transport.clone())
+        .set_deduplication(Deduplication::new(Duration::from_secs(60)));
+    let alert: Message = MessageBuilder::new("abc", "def", "disk full").set_title("db-1").build();
+
+    // A message that could not be sent opens no window: its retry goes through, then duplicates are suppressed
+    assert!(matches!(client.send(&alert).await, Err(PushoverError::Transport(_))));
+    assert!(client.send(&alert).await.is_ok());
+    assert!(matches!(client.send(&alert).await, Err(PushoverError::Suppressed { .. })));
+    assert_eq!(transport.inner.requests.lock().unwrap().len(), 2);
+    assert_eq!(client.deduplication_stats(), DeduplicationStats { forwarded: 1, suppressed: 1, summaries: 0 });
+}
+
+#[tokio::test]
+async fn test_client_deduplication_custom_key() {
+    let client: PushoverClient = PushoverClient::new()
+        .set_dry_run(true)
+        .set_deduplication(Deduplication::new(Duration::from_secs(60)).set_key(|message| message.title.clone().unwrap_or_default()));
+
+    assert!(client.send(&MessageBuilder::new("abc", "def", "load 12").set_title("db-1").build()).await.is_ok());
+    assert!(client.send(&MessageBuilder::new("abc", "def", "load 15").set_title("db-1").build()).await.is_err());
+    assert!(client.send(&MessageBuilder::new("abc", "def", "load 15").set_title("db-2").build()).await.is_ok());
+    assert_eq!(client.deduplication_stats().suppressed, 1);
+    assert_eq!(PushoverClient::new().deduplication_stats(), DeduplicationStats::default());
+}
+
+#[cfg(feature = "schedule")]
+#[test]
+fn test_schedule() {
+    use chrono::{NaiveTime, TimeZone, Utc, Weekday};
+    use crate::{OffHours, Schedule, ScheduleDecision};
+
+    let schedule: Schedule = Schedule::new(chrono_tz::Europe::Brussels);
+    let urgent: Message = MessageBuilder::new("abc", "def", "test message").set_priority(Priority::High).build();
+    let emergency: Message = MessageBuilder::new("abc", "def", "test message").set_priority(Priority::emergency()).build();
+
+    // Wednesday 2024-01-10, 10:00 in Brussels (UTC+1)
+    let business_time = Utc.with_ymd_and_hms(2024, 1, 10, 9, 0, 0).unwrap();
+    // Wednesday 2024-01-10, 23:00 in Brussels
+    let night = Utc.with_ymd_and_hms(2024, 1, 10, 22, 0, 0).unwrap();
+    assert!(schedule.is_business_time(business_time));
+    assert!(!schedule.is_business_time(night));
+
+    match schedule.apply(&urgent, business_time) {
+        ScheduleDecision::Send(message) => assert_eq!(message.priority, Some(1)),
+        other => panic!("Unexpected decision: {:?}", other),
+    }
+    match schedule.apply(&urgent, night) {
+        ScheduleDecision::Send(message) => assert_eq!(message.priority, Some(-1)),
+        other => panic!("Unexpected decision: {:?}", other),
+    }
+    match schedule.apply(&emergency, night) {
+        ScheduleDecision::Send(message) => assert_eq!(message.priority, Some(2)),
+        other => panic!("Unexpected decision: {:?}", other),
+    }
+    match schedule.clone().set_off_hours(OffHours::LowerPriority(Priority::Lowest)).apply(&urgent, night) {
+        ScheduleDecision::Send(message) => assert_eq!(message.priority, Some(-2)),
+        other => panic!("Unexpected decision: {:?}", other),
+    }
+
+    let deferring: Schedule = schedule
+        .set_business_days(&[Weekday::Mon])
+        .set_business_hours(NaiveTime::from_hms_opt(8, 0, 0).unwrap(), NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+        .set_off_hours(OffHours::Defer);
+    match deferring.apply(&urgent, night) {
+        // Monday 2024-01-15, 08:00 in Brussels
+        ScheduleDecision::Defer { until, message } => {
+            assert_eq!(until, Utc.with_ymd_and_hms(2024, 1, 15, 7, 0, 0).unwrap());
+            assert_eq!(message.priority, Some(1));
+        },
+        other => panic!("Unexpected decision: {:?}", other),
+    }
+    assert!(matches!(deferring.apply(&emergency, night), ScheduleDecision::Send(_)));
+}
+
+#[cfg(feature = "schedule")]
+#[tokio::test]
+async fn test_client_schedule_defer() {
+    use chrono::NaiveTime;
+    use crate::{OffHours, Schedule};
+    use std::time::SystemTime;
+
+    // Empty business hours -> Always off hours, the next opening being at midnight
+    let midnight: NaiveTime = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+    let client: PushoverClient = PushoverClient::new()
+        .set_dry_run(true)
+        .set_schedule(Schedule::new(chrono_tz::UTC).set_business_hours(midnight, midnight).set_off_hours(OffHours::Defer));
+
+    match client.send(&MessageBuilder::new("abc", "def", "test message").build()).await {
+        Err(PushoverError::Deferred { until }) => assert!(until > SystemTime::now()),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!(client.take_dry_run_requests().is_empty());
+
+    assert!(client.send(&MessageBuilder::new("abc", "def", "test message").set_priority(Priority::emergency()).build()).await.is_ok());
+    assert_eq!(client.take_dry_run_requests().len(), 1);
+}
+
+#[cfg(feature = "scheduler")]
+#[tokio::test]
+async fn test_scheduler() {
+    use crate::scheduler::{ScheduledJob, Scheduler, Trigger};
+    use std::time::SystemTime;
+
+    let store: std::path::PathBuf = std::env::temp_dir().join(format!("pushover-rs-scheduler-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&store);
+    let client: PushoverClient = PushoverClient::new().set_dry_run(true);
+    let scheduler: Scheduler = Scheduler::with_store(client.clone(), &store).unwrap();
+    let message: Message = MessageBuilder::new("abc", "def", "test message").build();
+
+    let due: String = scheduler.schedule_at(message.clone(), SystemTime::now() - Duration::from_secs(1)).unwrap();
+    let later: String = scheduler.schedule_at(message.clone(), SystemTime::now() + Duration::from_secs(3600)).unwrap();
+    let daily: String = scheduler.schedule_cron(message.clone(), "0 30 9 * * *").unwrap();
+    assert!(scheduler.schedule_cron(message.clone(), "every day").is_err());
+    assert_ne!(due, later);
+
+    let results = scheduler.run_pending().await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, due);
+    assert!(results[0].1.is_ok());
+    assert_eq!(client.take_dry_run_requests().len(), 1);
+
+    assert!(scheduler.cancel(&later).unwrap());
+    assert!(!scheduler.cancel(&later).unwrap());
+
+    let reloaded: Scheduler = Scheduler::with_store(PushoverClient::new(), &store).unwrap();
+    let jobs = reloaded.jobs();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].id, daily);
+    assert_eq!(jobs[0].trigger, Trigger::Cron("0 30 9 * * *".to_owned()));
+    assert_eq!(jobs[0].message.message, "test message".to_owned());
+
+    // A job whose next run can't be computed is dropped, without stopping the others
+    let broken: Vec<ScheduledJob> = vec![
+        ScheduledJob { id: "broken".into(), message: message.clone(), trigger: Trigger::Cron("every day".into()), next_run: SystemTime::now() - Duration::from_secs(1) },
+        ScheduledJob { id: "due".into(), message: message.clone(), trigger: Trigger::At(SystemTime::now() - Duration::from_secs(1)), next_run: SystemTime::now() - Duration::from_secs(1) },
+    ];
+    std::fs::write(&store, serde_json::to_vec(&broken).unwrap()).unwrap();
+    let reloaded: Scheduler = Scheduler::with_store(client.clone(), &store).unwrap();
+    let mut results = reloaded.run_pending().await.unwrap();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[0], (ref id, Err(PushoverError::InvalidInput(_))) if id == "broken"));
+    assert!(matches!(results[1], (ref id, Ok(_)) if id == "due"));
+    assert!(reloaded.jobs().is_empty());
+    assert!(reloaded.run_pending().await.unwrap().is_empty());
+    std::fs::remove_file(&store).unwrap();
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_instrumentation_redaction() {
+    use crate::pushover::instrumentation::{endpoint, redact};
+
+    assert_eq!(redact("uQiRzpo4DXghDmr9QzzfQu27cmVRsG"), "uQiR***".to_owned());
+    assert_eq!(redact("uQiRzpo4DXghDmr9QzzfQu27cmVRsG,gznej3rKEVAvPUxu9vvNnqpmZpokzF"), "uQiR***,gzne***".to_owned());
+    assert_eq!(redact("abc"), "***".to_owned());
+    assert_eq!(endpoint("https://api.pushover.net/1/teams.json?token=secret"), "https://api.pushover.net/1/teams.json");
+    assert_eq!(endpoint("https://api.pushover.net/1/messages.json"), "https://api.pushover.net/1/messages.json");
+}
+
+#[cfg(all(feature = "metrics", feature = "blocking", feature = "testing"))]
+#[test]
+fn test_client_metrics() {
+    use crate::testing::MockPushover;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    let mock: MockPushover = MockPushover::new().add_app_token("def").add_user("abc", &[]);
+    let client: crate::blocking::PushoverClient = crate::blocking::PushoverClient::new().set_transport(mock.clone());
+    let recorder: DebuggingRecorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || {
+        assert!(client.send(&MessageBuilder::new("abc", "def", "test message").set_priority(Priority::High).build()).is_ok());
+        assert!(client.send(&MessageBuilder::new("xyz", "def", "test message").set_priority(Priority::High).build()).is_err());
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let find = |name: &str| {
+        snapshot
+            .iter()
+            .find(|(key, _, _, _)| key.key().name() == name)
+            .map(|(key, _, _, value)| (key.key().labels().map(|label| format!("{}={}", label.key(), label.value())).collect::<Vec<String>>(), value))
+            .unwrap_or_else(|| panic!("Missing metric {}", name))
+    };
+
+    let (labels, sent) = find("pushover_messages_sent_total");
+    assert_eq!(labels, vec!["priority=1".to_owned(), "endpoint=/1/messages.json".to_owned()]);
+    assert_eq!(sent, &DebugValue::Counter(1));
+    let (labels, failed) = find("pushover_messages_failed_total");
+    assert!(labels.contains(&"error=invalid_user".to_owned()));
+    assert_eq!(failed, &DebugValue::Counter(1));
+    assert!(matches!(find("pushover_request_duration_seconds").1, DebugValue::Histogram(durations) if durations.len() == 1));
+    assert!(matches!(find("pushover_rate_limit_remaining").1, DebugValue::Gauge(_)));
+}
+
+#[cfg(feature = "tracing-layer")]
+#[test]
+fn test_tracing_layer() {
+    use crate::tracing_layer::{PushoverLayer, PushoverLayerBuilder};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let transport: FakeTransport = FakeTransport::ok();
+    let layer = PushoverLayerBuilder::new("abc", "def")
+        .set_rate_limit(1, Duration::from_secs(60))
+        .set_client(crate::blocking::PushoverClient::new().set_transport(transport.clone()))
+        .build();
+
+    let dispatch: tracing::Dispatch = tracing::Dispatch::new(tracing_subscriber::registry().with(layer));
+    tracing::dispatcher::with_default(&dispatch, || {
+        tracing::warn!(target: "billing", "ignored, below the level");
+        tracing::error!(target: "billing", invoice = 42, "database unreachable");
+        tracing::error!(target: "billing", "dropped by the rate limit");
+    });
+    dispatch.downcast_ref::<PushoverLayer>().unwrap().flush();
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["title"], "billing");
+    assert_eq!(body["message"], "ERROR database unreachable\ninvoice = 42");
+    assert_eq!(body["monospace"], 1);
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_logger() {
+    use crate::logger::{PushoverLogger, PushoverLoggerBuilder};
+    use log::{Level, Log, Record};
+
+    let transport: FakeTransport = FakeTransport::ok();
+    let logger: PushoverLogger = PushoverLoggerBuilder::new("abc", "def")
+        .set_batching(10, Duration::from_secs(60))
+        .set_client(crate::blocking::PushoverClient::new().set_transport(transport.clone()))
+        .build();
+
+    let log = |level: Level, message: &str| {
+        logger.log(&Record::builder().level(level).target("billing").args(format_args!("{}", message)).build());
+    };
+    log(Level::Info, "ignored, below the level");
+    log(Level::Warn, "slow database");
+    log(Level::Error, "database unreachable");
+    logger.flush();
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["title"], "billing (2 records)");
+    assert_eq!(body["message"], "WARN  billing: slow database\nERROR billing: database unreachable");
+    assert_eq!(body["priority"], 1);
+    assert_eq!(body["monospace"], 1);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_panic_hook() {
+    use crate::panic_hook::{backtrace_excerpt, PanicHookConfig};
+
+    // Described without installing the hook, which is process-wide and would see the other tests' panics
+    let config: PanicHookConfig = PanicHookConfig::new("abc", "def")
+        .set_title("daemon crashed")
+        .set_backtrace_lines(5);
+    let message: Message = config.panic_message("worker", "src/main.rs:12:5", "disk full");
+    assert_eq!(message.title, Some("daemon crashed".to_owned()));
+    assert_eq!(message.priority, Some(1));
+    assert_eq!(message.monospace, Some(1));
+    assert!(message.message.starts_with("thread 'worker' panicked at src/main.rs:12:5:\ndisk full\n\nBacktrace:\n"));
+    assert_eq!(message.message.lines().count(), 9);
+
+    let message: Message = PanicHookConfig::new("abc", "def")
+        .set_backtrace_lines(0)
+        .panic_message("<unnamed>", "an unknown location", "Box<dyn Any>");
+    assert_eq!(message.message, "thread '<unnamed>' panicked at an unknown location:\nBox<dyn Any>");
+    assert!(message.title.unwrap().ends_with(" panicked"));
+
+    // The backtrace starts at the panicking code, after the panic machinery
+    let backtrace: &str = "   0: std::backtrace::Backtrace::force_capture\n             at /rustc/library/std/src/backtrace.rs:310:13\n   \
+        1: std::panicking::begin_panic_handler::{{closure}}\n   \
+        2: std::sys::backtrace::__rust_end_short_backtrace\n   \
+        3: rust_begin_unwind\n   \
+        4: core::panicking::panic_fmt\n             at /rustc/library/core/src/panicking.rs:75:14\n   \
+        5: daemon::worker::run\n             at ./src/worker.rs:12:5\n   \
+        6: daemon::main";
+    assert_eq!(backtrace_excerpt(backtrace, 3), "   5: daemon::worker::run\n             at ./src/worker.rs:12:5\n   6: daemon::main");
+    assert_eq!(backtrace_excerpt("   0: daemon::main", 3), "   0: daemon::main");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_panic_hook_installed() {
+    use crate::panic_hook::{install_panic_hook, PanicHookConfig};
+    use std::panic::PanicHookInfo;
+
+    /// Keeps the messages sent, and panics on those about an explosion
+    #[derive(Clone, Default)]
+    struct ExplodingTransport {
+        messages: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl crate::blocking::Transport for ExplodingTransport {
+        fn send(&self, request: TransportRequest) -> Result<TransportResponse, PushoverError> {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let message: String = body["message"].as_str().unwrap_or_default().to_owned();
+            self.messages.lock().unwrap().push(message.clone());
+            if message.contains("explode") {
+                panic!("the transport failed to explode");
+            }
+            Ok(TransportResponse { status: 200, headers: Vec::new(), body: FakeTransport::ok().body.as_bytes().to_vec() })
+        }
+    }
+
+    // The hook is process-wide: only the panics of this test's thread are looked at, and the original hook is put back
+    type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+    let original: Arc<Hook> = Arc::new(std::panic::take_hook());
+    let test_thread: Option<String> = std::thread::current().name().map(str::to_owned);
+    let chained: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let (hook_original, hook_chained) = (original.clone(), chained.clone());
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name().map(str::to_owned) == test_thread {
+            hook_chained.lock().unwrap().push(info.to_string());
+        }
+        hook_original(info);
+    }));
+
+    let transport: ExplodingTransport = ExplodingTransport::default();
+    install_panic_hook(PanicHookConfig::new("abc", "def").set_client(crate::blocking::PushoverClient::new().set_transport(transport.clone())));
+    let first = std::panic::catch_unwind(|| panic!("disk full"));
+    // The sending thread panics too: that panic is not sent again
+    let second = std::panic::catch_unwind(|| panic!("about to explode"));
+    std::thread::sleep(Duration::from_millis(200));
+    std::panic::set_hook(Box::new(move |info| original(info)));
+
+    assert!(first.is_err() && second.is_err());
+    let chained = chained.lock().unwrap();
+    assert!(chained.iter().any(|panic| panic.contains("disk full")));
+    assert!(chained.iter().any(|panic| panic.contains("about to explode")));
+    let messages = transport.messages.lock().unwrap();
+    assert!(messages.iter().any(|message| message.contains("disk full")));
+    assert_eq!(messages.iter().filter(|message| message.contains("explode")).count(), 1);
+}
+
+#[cfg(all(feature = "alertmanager", feature = "testing"))]
+#[tokio::test]
+async fn test_alertmanager_relay() {
+    use crate::alertmanager::AlertmanagerRelay;
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new().add_app_token("def").add_user("abc", &[]);
+    let relay: AlertmanagerRelay = AlertmanagerRelay::new("abc", "def")
+        .set_client(PushoverClient::new().set_transport(mock.clone()));
+
+    let fired = relay.handle(include_bytes!("data/alertmanager_firing.json")).await.unwrap();
+    assert_eq!(fired.len(), 2);
+    assert!(fired.iter().all(|(_, result)| result.is_ok()));
+    let messages = mock.messages();
+    assert_eq!(messages[0].field("title"), Some("[FIRING] InstanceDown"));
+    assert_eq!(messages[0].field("message"), Some("db-1:9100 is down\n\ndb-1:9100 of job node has been down for more than 5 minutes."));
+    assert_eq!(messages[0].field("priority"), Some("2"));
+    assert_eq!(messages[0].field("sound"), Some("siren"));
+    assert_eq!(messages[0].field("url_title"), Some("Source"));
+    assert_eq!(messages[1].field("priority"), Some("1"));
+    assert_eq!(messages[1].field("url"), None);
+    assert_eq!(mock.receipts().len(), 1);
+
+    // Notified again while firing, the emergency alert keeps its receipt rather than being sent again
+    let refired = relay.handle(include_bytes!("data/alertmanager_firing.json")).await.unwrap();
+    assert_eq!(refired.len(), 1);
+    assert_eq!(refired[0].0, fired[1].0);
+    assert_eq!(mock.messages().len(), 3);
+    assert_eq!(mock.receipts().len(), 1);
+
+    let resolved = relay.handle(include_bytes!("data/alertmanager_resolved.json")).await.unwrap();
+    // The emergency retries are cancelled, then the resolution is notified
+    assert_eq!(resolved.len(), 2);
+    assert!(mock.receipts()[0].cancelled);
+    let messages = mock.messages();
+    assert_eq!(messages[3].field("title"), Some("[RESOLVED] InstanceDown"));
+    assert_eq!(messages[3].field("priority"), Some("0"));
+    assert_eq!(messages[3].field("sound"), None);
+
+    assert!(matches!(relay.handle(b"{}").await, Err(PushoverError::InvalidInput(_))));
+}
+
+#[cfg(feature = "alertmanager")]
+#[tokio::test]
+async fn test_alertmanager_relay_refires_expired_or_acknowledged() {
+    use std::time::{Duration, SystemTime};
+    use crate::alertmanager::{AlertmanagerPayload, AlertmanagerRelay};
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new().add_app_token("def").add_user("abc", &[]);
+    let relay: AlertmanagerRelay = AlertmanagerRelay::new("abc", "def")
+        .set_client(PushoverClient::new().set_transport(mock.clone()));
+    let payload: AlertmanagerPayload = serde_json::from_slice(include_bytes!("data/alertmanager_firing.json")).unwrap();
+    let now: SystemTime = SystemTime::now();
+
+    relay.relay_at(&payload, now).await;
+    assert_eq!(mock.receipts().len(), 1);
+
+    // Still retried: only the other alert is sent again
+    relay.relay_at(&payload, now + Duration::from_secs(60)).await;
+    assert_eq!(mock.messages().len(), 3);
+    assert_eq!(mock.receipts().len(), 1);
+
+    // Its retries expired: the emergency alert is sent again, with a new receipt
+    let expired: SystemTime = now + Duration::from_secs(3 * 3600 + 1);
+    let refired = relay.relay_at(&payload, expired).await;
+    assert_eq!(refired.len(), 2);
+    assert_eq!(mock.messages()[3].field("priority"), Some("2"));
+    assert_eq!(mock.receipts().len(), 2);
+
+    // Acknowledged: it is sent again too, still while firing
+    mock.acknowledge(&mock.receipts()[1].receipt);
+    let refired = relay.relay_at(&payload, expired + Duration::from_secs(60)).await;
+    assert_eq!(refired.len(), 2);
+    assert_eq!(mock.receipts().len(), 3);
+
+    // The receipt tracked last is the one cancelled on resolution
+    relay.handle(include_bytes!("data/alertmanager_resolved.json")).await.unwrap();
+    let receipts = mock.receipts();
+    assert!(!receipts[1].cancelled);
+    assert!(receipts[2].cancelled);
+}
+
+#[cfg(feature = "alertmanager")]
+#[test]
+fn test_alertmanager_template() {
+    use crate::alertmanager::{AlertmanagerPayload, AlertmanagerRelay, SeverityRoute};
+
+    let payload: AlertmanagerPayload = serde_json::from_slice(include_bytes!("data/alertmanager_firing.json")).unwrap();
+    let relay: AlertmanagerRelay = AlertmanagerRelay::new("abc", "def")
+        .set_severity("WARNING", SeverityRoute::new(Priority::Normal, Some(PushoverSound::FALLING)))
+        .set_template(MessageTemplate::new("{annotations.summary} ({labels.job})").set_title("{status}: {labels.instance}"));
+
+    let message: Message = relay.message_for(&payload.alerts[0]).unwrap();
+    assert_eq!(message.title, Some("firing: db-1:9100".to_owned()));
+    assert_eq!(message.message, "db-1:9100 is down (node)".to_owned());
+    assert_eq!(message.priority, Some(2));
+    assert_eq!(message.retry, Some(30));
+
+    // The second alert has no job label
+    assert!(matches!(relay.message_for(&payload.alerts[1]), Err(PushoverError::Template(_))));
+    let relay: AlertmanagerRelay = relay.set_template(MessageTemplate::new("{annotations.summary}"));
+    let message: Message = relay.message_for(&payload.alerts[1]).unwrap();
+    assert_eq!(message.priority, Some(0));
+    assert_eq!(message.sound, Some("falling".to_owned()));
+}
+
+#[cfg(feature = "webhook")]
+#[tokio::test]
+async fn test_webhook_gateway() {
+    use crate::webhook::{PriorityRule, WebhookGateway, WebhookRoute};
+    use std::collections::BTreeMap;
+
+    let client: PushoverClient = PushoverClient::new().set_dry_run(true);
+    let gateway: WebhookGateway = WebhookGateway::new("abc", "def")
+        .set_client(client.clone())
+        .add_route(WebhookRoute::new("/github", "$.head_commit.message")
+            .set_title("$.repository.full_name")
+            .set_url("$.compare", Some("See the changes"))
+            .set_priority(PriorityRule::Mapped {
+                field: "$.ref".to_owned(),
+                values: BTreeMap::from([("refs/heads/main".to_owned(), Priority::High)]),
+                default: Some(Priority::Low),
+            }))
+        .add_route(WebhookRoute::new("/sentry", "$.data.event.exception.values[0].value")
+            .set_title("$[\"project.name\"]")
+            .set_priority(PriorityRule::Fixed(Priority::High))
+            .set_sound("siren")
+            .set_user_key("ops"));
+
+    let push: &str = r#"{"ref":"refs/heads/main","compare":"https://github.com/o/r/compare/a...b","repository":{"full_name":"o/r"},"head_commit":{"message":"Fix the build"}}"#;
+    assert!(gateway.handle("/github", push.as_bytes()).await.is_ok());
+    match &client.take_dry_run_requests()[0].body {
+        PreparedBody::Json(body) => {
+            assert_eq!(body["title"], "o/r");
+            assert_eq!(body["message"], "Fix the build");
+            assert_eq!(body["url"], "https://github.com/o/r/compare/a...b");
+            assert_eq!(body["url_title"], "See the changes");
+            assert_eq!(body["priority"], 1);
+        },
+        other => panic!("Unexpected body: {:?}", other),
+    }
+
+    let branch_push: serde_json::Value = serde_json::json!({ "ref": "refs/heads/feature", "head_commit": { "message": "WIP" } });
+    let message: Message = gateway.message_for("/github", &branch_push).unwrap();
+    assert_eq!(message.priority, Some(-1));
+    assert_eq!(message.title, None);
+
+    let issue: serde_json::Value = serde_json::json!({ "project.name": "api", "data": { "event": { "exception": { "values": [{ "value": "KeyError: 'id'" }] } } } });
+    let message: Message = gateway.message_for("/sentry", &issue).unwrap();
+    assert_eq!(message.user_key, "ops".to_owned());
+    assert_eq!(message.title, Some("api".to_owned()));
+    assert_eq!(message.message, "KeyError: 'id'".to_owned());
+    assert_eq!(message.sound, Some("siren".to_owned()));
+
+    assert!(matches!(gateway.message_for("/sentry", &serde_json::json!({})), Err(PushoverError::InvalidInput(_))));
+    assert!(matches!(gateway.message_for("/unknown", &issue), Err(PushoverError::InvalidInput(_))));
+    assert!(gateway.handle("/github", b"not json").await.is_err());
+}
+
+#[cfg(all(feature = "webhook", feature = "toml"))]
+#[test]
+fn test_webhook_gateway_from_toml() {
+    use crate::webhook::WebhookGateway;
+
+    let gateway: WebhookGateway = WebhookGateway::from_toml_str(include_str!("../../examples/data/webhook_gateway_example.toml")).unwrap();
+    let alert: serde_json::Value = serde_json::json!({ "title": "[FIRING:1] High load", "message": "Load is 12", "status": "firing" });
+    let message: Message = gateway.message_for("/grafana", &alert).unwrap();
+    assert_eq!(message.title, Some("[FIRING:1] High load".to_owned()));
+    assert_eq!(message.priority, Some(1));
+    assert_eq!(message.url, None);
+
+    // An invalid priority is refused when loading the configuration, rather than sent as a normal one
+    let invalid: &str = "user_key = \"abc\"\napp_token = \"def\"\n[[routes]]\npath = \"/a\"\nmessage = \"b\"\npriority = 5\n";
+    assert!(WebhookGateway::from_toml_str(invalid).is_err());
+}
+
+#[cfg(feature = "smtp")]
+#[test]
+fn test_smtp_bridge() {
+    use crate::smtp::{SmtpBridge, SmtpRecipient};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    let transport: FakeTransport = FakeTransport::ok();
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let bridge: SmtpBridge = SmtpBridge::new("abc", "def")
+        .set_recipient("Phone", SmtpRecipient::Device("iphone".to_owned()))
+        .set_recipient("ops", SmtpRecipient::UserKey("group".to_owned()))
+        .set_client(crate::blocking::PushoverClient::new().set_transport(transport.clone()));
+    std::thread::spawn(move || bridge.serve(listener));
+
+    let mut stream: TcpStream = TcpStream::connect(address).unwrap();
+    let mut reader: BufReader<TcpStream> = BufReader::new(stream.try_clone().unwrap());
+    let mut exchange = |command: &str| -> String {
+        if !command.is_empty() {
+            stream.write_all(command.as_bytes()).unwrap();
+        }
+        let mut reply: String = String::new();
+        loop {
+            let mut line: String = String::new();
+            reader.read_line(&mut line).unwrap();
+            reply.push_str(&line);
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return reply;
+            }
+        }
+    };
+
+    assert!(exchange("").starts_with("220 "));
+    assert!(exchange("EHLO client\r\n").contains("250 8BITMIME"));
+    assert!(exchange("RCPT TO:<phone@localhost>\r\n").starts_with("503 "));
+
+    /* E-mail with an image, to a device */
+    assert!(exchange("MAIL FROM:<nas@localhost>\r\n").starts_with("250 "));
+    assert!(exchange("RCPT TO:<phone@localhost>\r\n").starts_with("250 "));
+    assert!(exchange("DATA\r\n").starts_with("354 "));
+    let email: &str = "From: nas@localhost\r\n\
+        To: phone@localhost\r\n\
+        Subject: Backup failed\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+        \r\n\
+        --b\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        Disk full\r\n\
+        ..hidden\r\n\
+        --b\r\n\
+        Content-Type: image/png\r\n\
+        Content-Disposition: attachment; filename=\"graph.png\"\r\n\
+        Content-Transfer-Encoding: base64\r\n\
+        \r\n\
+        iVBORw0KGgo=\r\n\
+        --b--\r\n\
+        .\r\n";
+    assert!(exchange(email).starts_with("250 "));
+
+    /* Plain e-mail, to a group */
+    assert!(exchange("MAIL FROM:<cron@localhost>\r\n").starts_with("250 "));
+    assert!(exchange("RCPT TO:<ops@localhost>\r\n").starts_with("250 "));
+    assert!(exchange("DATA\r\n").starts_with("354 "));
+    assert!(exchange("Subject: Job done\r\n\r\nAll good\r\n.\r\n").starts_with("250 "));
+    assert!(exchange("QUIT\r\n").starts_with("221 "));
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+
+    let form: String = String::from_utf8_lossy(&requests[0].body).into_owned();
+    assert!(form.contains("name=\"user\"\r\n\r\nabc\r\n"));
+    assert!(form.contains("name=\"title\"\r\n\r\nBackup failed\r\n"));
+    assert!(form.contains("name=\"message\"\r\n\r\nDisk full\r\n.hidden\r\n"));
+    assert!(form.contains("name=\"device\"\r\n\r\niphone\r\n"));
+    assert!(requests[0].body.windows(4).any(|window| window == b"\x89PNG"));
+
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body["user"], "group");
+    assert_eq!(body["title"], "Job done");
+    assert_eq!(body["message"], "All good");
+    assert!(body.get("device").is_none());
+}
+
+#[cfg(feature = "smtp")]
+#[test]
+fn test_smtp_bridge_failures() {
+    use crate::smtp::{data_reply, SmtpBridge, SmtpRecipient};
+    use crate::testing::MockPushover;
+
+    let mock: MockPushover = MockPushover::new().add_app_token("def").add_user("abc", &[]);
+    let bridge: SmtpBridge = SmtpBridge::new("abc", "def")
+        .set_recipient("ops", SmtpRecipient::UserKey("nobody".to_owned()))
+        .set_client(crate::blocking::PushoverClient::new().set_transport(mock.clone()));
+
+    /* An image too large for the API is left out, the text still goes through */
+    // About 3 MB of zeroes, base64-encoded
+    let image: String = format!("{}\r\n", "A".repeat(76)).repeat(55_000);
+    let email: String = format!(
+        "Subject: Camera\r\nMIME-Version: 1.0\r\nContent-Type: multipart/mixed; boundary=\"b\"\r\n\r\n\
+        --b\r\nContent-Type: text/plain\r\n\r\nMotion detected\r\n\
+        --b\r\nContent-Type: image/jpeg\r\nContent-Transfer-Encoding: base64\r\n\r\n{}--b--\r\n",
+        image,
+    );
+    let results = bridge.forward(&["me@localhost".to_owned()], email.as_bytes());
+    assert!(results[0].1.is_ok());
+    let messages = mock.messages();
+    assert_eq!(messages[0].field("message"), Some("Motion detected"));
+    assert_eq!(messages[0].attachment, None);
+
+    /* Once a recipient got it, the e-mail is accepted */
+    let results = bridge.forward(&["me@localhost".to_owned(), "ops@localhost".to_owned()], b"Subject: Job done\r\n\r\nAll good\r\n");
+    assert!(results[0].1.is_ok());
+    assert!(matches!(results[1].1, Err(PushoverError::InvalidUser { .. })));
+    assert!(data_reply(&results).starts_with("250 OK: partly forwarded"));
+    assert!(data_reply(&results[1..]).starts_with("554 "));
+
+    /* Unreachable, unavailable or rate limited: the client tries again later */
+    let temporary = |error: PushoverError| data_reply(&[("me@localhost".to_owned(), Err(error))]);
+    assert!(temporary(PushoverError::transport("connection reset")).starts_with("451 "));
+    assert!(temporary(PushoverError::Api { request: "r".into(), errors: vec![], http_status: Some(429) }).starts_with("451 "));
+    assert!(temporary(PushoverError::Api { request: "r".into(), errors: vec![], http_status: Some(503) }).starts_with("451 "));
+    assert!(temporary(PushoverError::Api { request: "r".into(), errors: vec![], http_status: Some(400) }).starts_with("554 "));
+}
+
+#[test]
+fn test_message_round_trip() {
+    let message: Message = MessageBuilder::new("abc", "def", "Disk full")
+        .set_title("web-1")
+        .set_priority(Priority::emergency().set_retry(Duration::from_secs(60)))
+        .set_sound(PushoverSound::SIREN)
+        .set_monospace(true)
+        .build();
+    let expected: serde_json::Value = serde_json::to_value(&message).unwrap();
+
+    let from_json: Message = serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&from_json).unwrap(), expected);
+    let from_ron: Message = ron::from_str(&ron::to_string(&message).unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&from_ron).unwrap(), expected);
+    #[cfg(feature = "toml")]
+    {
+        let from_toml: Message = toml::from_str(&toml::to_string(&message).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&from_toml).unwrap(), expected);
+    }
+
+    /* Builders */
+    let rebuilt: Message = MessageBuilder::try_from(&message).unwrap().set_title("web-2").build();
+    assert_eq!(rebuilt.title, Some("web-2".to_owned()));
+    assert_eq!(rebuilt.expire, Some(10800));
+    let configured: Message = MessageBuilder::from_value(&serde_json::json!({
+        "app_token": "def",
+        "user_key": "abc",
+        "message": "Backup done",
+        "priority": -1,
+    })).unwrap().build();
+    assert_eq!(configured.app_token, "def");
+    assert_eq!(configured.priority, Some(-1));
+
+    /* Validation */
+    let error: PushoverError = MessageBuilder::from_value(&serde_json::json!({"token": "def", "user": "abc", "message": "test", "priority": 5})).unwrap_err();
+    assert!(error.to_string().contains("priority must be between -2 and 2"));
+    assert!(serde_json::from_str::<Message>(r#"{"token": "def", "user": "", "message": "test"}"#).is_err());
+    assert!(serde_json::from_str::<Message>(r#"{"token": "def", "user": "abc", "message": "test", "retry": 5}"#).is_err());
+    let mut invalid: Message = message.clone();
+    invalid.message = "x".repeat(1025);
+    assert!(MessageBuilder::try_from(&invalid).is_err());
+}
+
+#[test]
+fn test_attachment_message_round_trip() {
+    let message: AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "Camera 1")
+        .set_priority(Priority::High)
+        .set_timestamp(UNIX_EPOCH + Duration::from_secs(1635861224))
+        .set_attachment("Cargo.toml".to_owned())
+        .build()
+        .unwrap();
+
+    let from_json: AttachmentMessage = serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+    assert_eq!(from_json.priority, Some(1));
+    assert_eq!(from_json.timestamp, Some(1635861224));
+    let from_ron: AttachmentMessage = ron::from_str(&ron::to_string(&message).unwrap()).unwrap();
+    assert_eq!(from_ron.attachment, message.attachment);
+
+    /* Numbers are accepted for the numeric fields */
+    let configured: AttachmentMessage = AttachmentMessageBuilder::from_value(&serde_json::json!({
+        "token": "def",
+        "user": "abc",
+        "message": "Camera 2",
+        "attachment": "Cargo.toml",
+        "priority": 2,
+        "retry": 60,
+    })).unwrap().build().unwrap();
+    assert_eq!(configured.priority, Some(2));
+    assert_eq!(configured.retry, Some(60));
+    assert_eq!(configured.expire, Some(10800));
+
+    let mut invalid: AttachmentMessage = message.clone();
+    invalid.expire = Some(5);
+    assert!(AttachmentMessageBuilder::try_from(&invalid).is_err());
+    assert!(serde_json::from_str::<AttachmentMessage>(r#"{"app_token": "def", "user_key": "abc", "message": "test", "attachment": ""}"#).is_err());
+}
+
+#[test]
+fn test_emergency_fields_validation() {
+    let emergency: Message = MessageBuilder::new("abc", "def", "test message").set_priority(Priority::emergency()).build();
+    assert!(emergency.validate().is_ok());
+
+    // An emergency message needs both its retry and expire
+    let mut without_expire: Message = emergency.clone();
+    without_expire.expire = None;
+    assert!(matches!(without_expire.validate(), Err(PushoverError::InvalidInput(_))));
+    assert!(serde_json::from_str::<Message>(r#"{"token": "def", "user": "abc", "message": "test", "priority": 2, "retry": 60}"#).is_err());
+
+    // Other priorities have none
+    let mut high: Message = emergency.clone();
+    high.priority = Some(1);
+    assert!(matches!(high.validate(), Err(PushoverError::InvalidInput(_))));
+    high.retry = None;
+    high.expire = None;
+    assert!(high.validate().is_ok());
+
+    let attachment: AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .set_priority(Priority::emergency())
+        .build()
+        .unwrap();
+    assert!(attachment.validate().is_ok());
+    let mut normal: AttachmentMessage = attachment.clone();
+    normal.priority = Some(0);
+    assert!(matches!(normal.validate(), Err(PushoverError::InvalidInput(_))));
+    let mut without_retry: AttachmentMessage = attachment;
+    without_retry.retry = None;
+    assert!(matches!(without_retry.validate(), Err(PushoverError::InvalidInput(_))));
+}
+
+#[test]
+fn test_priority() {
+    let priority: Priority = Priority::emergency()
+        .set_retry(Duration::from_secs(10)) // Below the minimum -> Should be raised to 30
+        .set_expire(Duration::from_secs(3600))
+        .set_callback("https://example.com/acknowledged")
+        .add_tag("db")
+        .add_tag("web-1");
+
+    let message: Message = MessageBuilder::new("abc", "def", "test message").set_priority(priority.clone()).build();
+    assert_eq!(message.priority, Some(2));
+    assert_eq!(message.retry, Some(30));
+    assert_eq!(message.expire, Some(3600));
+    assert_eq!(message.callback, Some("https://example.com/acknowledged".to_owned()));
+    assert_eq!(message.tags, Some("db,web-1".to_owned()));
+
+    /* Emergency parameters are dropped with the emergency priority */
+    let lowered: Message = MessageBuilder::try_from(&message).unwrap().set_priority(Priority::High).build();
+    assert_eq!(lowered.priority, Some(1));
+    assert_eq!((lowered.retry, lowered.expire, lowered.callback, lowered.tags), (None, None, None, None));
+    assert_eq!(Priority::High.set_callback("https://example.com").add_tag("db"), Priority::High);
+
+    let attachment: AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .set_priority(priority)
+        .build()
+        .unwrap();
+    let fields: Vec<(&str, String)> = attachment.form_fields();
+    assert!(fields.contains(&("priority", "2".to_owned())));
+    assert!(fields.contains(&("retry", "30".to_owned())));
+    assert!(fields.contains(&("expire", "3600".to_owned())));
+    assert!(fields.contains(&("callback", "https://example.com/acknowledged".to_owned())));
+    assert!(fields.contains(&("tags", "db,web-1".to_owned())));
+
+    assert_eq!(Priority::try_from(-2).unwrap(), Priority::Lowest);
+    assert_eq!(Priority::try_from(2).unwrap(), Priority::emergency());
+    assert!(Priority::try_from(3).is_err());
+    assert_eq!(Priority::emergency().level(), 2);
+}
+
+#[test]
+fn test_time_setters() {
+    let message: Message = MessageBuilder::new("abc", "def", "test message")
+        .set_priority(Priority::emergency()
+            .set_retry(Duration::from_millis(90_500)) // A started second counts
+            .set_expire(Duration::from_secs(24 * 3600))) // Above the maximum -> Should be lowered to 3 hours
+        .set_ttl(Duration::from_millis(200))
+        .set_timestamp(UNIX_EPOCH + Duration::from_millis(1_635_861_224_999))
+        .build();
+    assert_eq!(message.retry, Some(91));
+    assert_eq!(message.expire, Some(10800));
+    assert_eq!(message.ttl, Some(1));
+    assert_eq!(message.timestamp, Some(1635861224));
+
+    let cleared: Message = MessageBuilder::try_from(&message).unwrap()
+        .set_ttl(Duration::ZERO)
+        .set_timestamp(UNIX_EPOCH - Duration::from_secs(1))
+        .build();
+    assert_eq!((cleared.ttl, cleared.timestamp), (None, None));
+
+    let priority: Priority = Priority::emergency().set_retry(Duration::from_secs(45));
+    assert!(matches!(priority, Priority::Emergency { retry, .. } if retry == Duration::from_secs(45)));
+
+    #[cfg(feature = "chrono")]
+    {
+        let time = chrono::DateTime::from_timestamp(1635861224, 0).unwrap();
+        let message: Message = MessageBuilder::new("abc", "def", "test message")
+            .set_timestamp(time)
+            .set_ttl(chrono::TimeDelta::minutes(2))
+            .build();
+        assert_eq!((message.timestamp, message.ttl), (Some(1635861224), Some(120)));
+        let message: Message = MessageBuilder::new("abc", "def", "test message").set_ttl(chrono::TimeDelta::minutes(-2)).build();
+        assert_eq!(message.ttl, None);
+    }
+
+    #[cfg(feature = "time")]
+    {
+        let time = time::OffsetDateTime::from_unix_timestamp(1635861224).unwrap();
+        let attachment: AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+            .set_attachment("Cargo.toml".into())
+            .set_timestamp(time)
+            .set_ttl(time::Duration::hours(1))
+            .build()
+            .unwrap();
+        assert_eq!((attachment.timestamp, attachment.ttl), (Some(1635861224), Some(3600)));
+    }
+}
+
+#[test]
+fn test_devices() {
+    let message: Message = MessageBuilder::new("abc", "def", "test message")
+        .set_devices(["iphone", "pixel_8"])
+        .add_device("work-laptop")
+        .add_device("iphone") // Already set -> Should not be repeated
+        .build();
+    assert_eq!(message.device, Some("iphone,pixel_8,work-laptop".to_owned()));
+    assert_eq!(serde_json::to_value(&message).unwrap()["device"], "iphone,pixel_8,work-laptop");
+    assert!(MessageBuilder::try_from(&message).unwrap().try_build().is_ok());
+
+    /* Exclusions */
+    let excluded: Message = MessageBuilder::new("abc", "def", "test message")
+        .exclude_device("ipad")
+        .add_device("-desktop")
+        .build();
+    assert_eq!(excluded.device, Some("-ipad,-desktop".to_owned()));
+    assert_eq!(MessageBuilder::new("abc", "def", "test message").set_device("a,b").remove_device().build().device, None);
+
+    /* Validation */
+    assert!(MessageBuilder::new("abc", "def", "test message").add_device("my phone").try_build().is_err());
+    assert!(MessageBuilder::new("abc", "def", "test message").add_device(&"x".repeat(26)).try_build().is_err());
+    assert!(serde_json::from_str::<Message>(r#"{"token": "def", "user": "abc", "message": "test", "device": "iphone,-"}"#).is_err());
+
+    let attachment: AttachmentMessage = AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .set_devices(vec!["iphone".to_owned(), "-ipad".to_owned()])
+        .build()
+        .unwrap();
+    assert!(attachment.form_fields().contains(&("device", "iphone,-ipad".to_owned())));
+    assert!(AttachmentMessageBuilder::new("abc", "def", "test message")
+        .set_attachment("Cargo.toml".into())
+        .add_device("iphone!")
+        .build()
+        .is_err());
+}
@@ -1,7 +1,10 @@
+use std::time::Duration;
 use crate::tests::setup::{TestData, read_test_data};
-use crate::{send_pushover_request, send_pushover_request_with_attachment, send_pushover_request_with_attachment_async};
+use crate::{PushoverClient, send_pushover_request, send_pushover_request_with_attachment_async};
+#[cfg(feature = "blocking")]
+use crate::send_pushover_request_with_attachment;
 use crate::{
-    pushover::data::{Message, MessageBuilder, AttachmentMessage, AttachmentMessageBuilder, PushoverResponse}
+    pushover::data::{Message, MessageBuilder, AttachmentMessage, AttachmentMessageBuilder, Priority, PushoverResponse}
 };
 
 #[test]
@@ -13,7 +16,7 @@ pub fn test_testdata_readability() {
 #[tokio::test]
 async fn test_send_minimal_message_specific_device() {
     // Device name is set on the Pushover dashboard.
-    // You can join multiple singular devices by separating them with a comma (eg: "MyPhone,MyOtherPhone")
+    // Several devices can be targeted with add_device or set_devices
     let device_name: &str = "MyPhone";
     if let Ok(credentials) = read_test_data() {
         let message: Message = MessageBuilder::new(
@@ -49,13 +52,13 @@ async fn test_send_request_minimal_message() {
 #[tokio::test]
 async fn test_send_request_min_message_with_ttl() {
     if let Ok(credentials) = read_test_data() {
-        let ttl: u32 = 5;
+        let ttl: u64 = 5;
         let message: Message = MessageBuilder::new(
             credentials.user_key.as_str(),
             credentials.app_token.as_str(),
             format!("Test from pushover-rs with {ttl} seconds TTL").as_str(),
         )
-            .set_ttl(ttl)
+            .set_ttl(Duration::from_secs(ttl))
             .build();
         let response = send_pushover_request(message).await;
         assert!(response.is_ok());
@@ -72,9 +75,7 @@ async fn test_send_request_emergency_priority() {
             credentials.app_token.as_str(),
             "Server down wake up!!!",
         )
-            .set_priority(2)
-            .set_retry(30)
-            .set_expire(60)
+            .set_priority(Priority::emergency().set_retry(Duration::from_secs(30)).set_expire(Duration::from_secs(60)))
             .build();
         let response = send_pushover_request(message).await;
         assert!(response.is_ok());
@@ -102,6 +103,7 @@ async fn test_send_bad_request() {
 }
 
 #[test]
+#[cfg(feature = "blocking")]
 fn test_send_with_good_attachment() {
     if let Ok(credentials) = read_test_data() {
         let attachment_path: String = "./testdata/attachment_test.jpg".to_owned();
@@ -111,7 +113,7 @@ fn test_send_with_good_attachment() {
             "Test from pushover-rs, with attachment and ttl",
         )
         .set_attachment(attachment_path)
-        .set_ttl(10)
+        .set_ttl(Duration::from_secs(10))
         .build()
         .unwrap();
 
@@ -122,7 +124,9 @@ fn test_send_with_good_attachment() {
         panic!("Could not read test data.");
     }
 }
+
 #[test]
+#[cfg(feature = "blocking")]
 fn test_send_with_good_attachment_and_emergency_priority() {
     if let Ok(credentials) = read_test_data() {
         let attachment_path: String = "./testdata/attachment_test.jpg".to_owned();
@@ -132,9 +136,7 @@ fn test_send_with_good_attachment_and_emergency_priority() {
             "Test from pushover-rs, with attachment and ttl",
         )
             .set_attachment(attachment_path)
-            .set_priority(2)
-            .set_retry(30)
-            .set_expire(120)
+            .set_priority(Priority::emergency().set_retry(Duration::from_secs(30)).set_expire(Duration::from_secs(120)))
             .build()
             .unwrap();
 
@@ -156,7 +158,7 @@ async fn test_send_with_good_attachment_async() {
             "<b>Test from pushover-rs</b>, with <font color='#FF0000'>attachment</font>, <font color='#00FF00'>ttl</font> and <font color='#0000FF'>async</font>",
         )
             .set_attachment(attachment_path)
-            .set_ttl(60)
+            .set_ttl(Duration::from_secs(60))
             .build()
             .unwrap();
 
@@ -166,4 +168,47 @@ async fn test_send_with_good_attachment_async() {
     } else {
         panic!("Could not read test data.");
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_client_send_all() {
+    if let Ok(credentials) = read_test_data() {
+        let client: PushoverClient = PushoverClient::new()
+            .set_concurrency(2)
+            .set_requests_per_second(2);
+        let messages: Vec<Message> = (1..=3)
+            .map(|i| MessageBuilder::new(
+                credentials.user_key.as_str(),
+                credentials.app_token.as_str(),
+                format!("Test {i}/3 from pushover-rs bulk sending.").as_str(),
+            ).build())
+            .collect();
+        let responses = client.send_all(messages).await;
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|response| response.is_ok()));
+    } else {
+        panic!("Could not read test data.");
+    }
+}
+
+#[test]
+#[cfg(feature = "blocking")]
+fn test_blocking_client_send_all() {
+    if let Ok(credentials) = read_test_data() {
+        let client: crate::blocking::PushoverClient = crate::blocking::PushoverClient::new()
+            .set_concurrency(2)
+            .set_requests_per_second(2);
+        let messages: Vec<Message> = (1..=3)
+            .map(|i| MessageBuilder::new(
+                credentials.user_key.as_str(),
+                credentials.app_token.as_str(),
+                format!("Test {i}/3 from pushover-rs blocking bulk sending.").as_str(),
+            ).build())
+            .collect();
+        let responses = client.send_all(messages);
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|response| response.is_ok()));
+    } else {
+        panic!("Could not read test data.");
+    }
+}
@@ -0,0 +1,269 @@
+/*!
+A tracing-subscriber Layer forwarding log events to Pushover (Requires the `tracing-layer` feature)
+
+```no_run
+use tracing_subscriber::prelude::*;
+use pushover_rs::tracing_layer::PushoverLayerBuilder;
+
+let layer = PushoverLayerBuilder::new("<USER_KEY>", "<APP_TOKEN>")
+    .set_level(tracing::Level::ERROR)
+    .build();
+tracing_subscriber::registry().with(layer).init();
+```
+ */
+
+use std::fmt::Write;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::blocking::PushoverClient;
+use crate::pushover::constants::{PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::text::truncate;
+use crate::{Message, MessageBuilder, Priority};
+
+/// The name of the thread sending the messages
+const PUSHOVER_LAYER_THREAD_NAME: &str = "pushover-layer";
+/// By default, at most this many events are waiting to be sent, the next ones are dropped
+const PUSHOVER_LAYER_DEFAULT_CAPACITY: usize = 32;
+/// By default, at most this many messages are sent per minute
+const PUSHOVER_LAYER_DEFAULT_MESSAGES_PER_MINUTE: u32 = 10;
+/// The longest time flush waits for the pending messages to be sent
+const PUSHOVER_LAYER_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+Converts the events of at least a given level into Pushover messages, sent from a background thread.
+
+Logging never blocks: the events waiting to be sent are kept in a bounded channel, and the events
+exceeding its capacity or the rate limit are dropped. The crate's own events are never forwarded.
+ */
+pub struct PushoverLayer {
+    user_key: String,
+    app_token: String,
+    level: Level,
+    priority: Option<Priority>,
+    rate_limit: Mutex<RateLimit>,
+    sender: SyncSender<Command>,
+}
+
+/**
+Helps configure a PushoverLayer.
+ */
+#[derive(Debug)]
+pub struct PushoverLayerBuilder {
+    user_key: String,
+    app_token: String,
+    level: Level,
+    priority: Option<Priority>,
+    max_messages: u32,
+    per: Duration,
+    capacity: usize,
+    client: PushoverClient,
+}
+
+/// What the sending thread is asked to do
+enum Command {
+    Send(Box<Message>),
+    /// Answers once the messages received before are sent
+    Flush(SyncSender<()>),
+}
+
+/// Allows a number of messages per fixed window
+#[derive(Debug)]
+struct RateLimit {
+    max_messages: u32,
+    per: Duration,
+    window_start: Instant,
+    sent: u32,
+}
+
+impl PushoverLayerBuilder {
+    /// Forwards the ERROR events to this user, at most 10 per minute
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        Self {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            level: Level::ERROR,
+            priority: None,
+            max_messages: PUSHOVER_LAYER_DEFAULT_MESSAGES_PER_MINUTE,
+            per: Duration::from_secs(60),
+            capacity: PUSHOVER_LAYER_DEFAULT_CAPACITY,
+            client: PushoverClient::new(),
+        }
+    }
+
+    /// Forwards the events of this level and the more severe ones
+    pub fn set_level(mut self, level: Level) -> PushoverLayerBuilder {
+        self.level = level;
+        self
+    }
+
+    /// Sets the priority of the messages (emergency messages are not supported, they are sent with a high priority)
+    pub fn set_priority(mut self, priority: Priority) -> PushoverLayerBuilder {
+        self.priority = Some(priority.without_emergency());
+        self
+    }
+
+    /// Sends at most `max_messages` messages per period, the other events are dropped
+    pub fn set_rate_limit(mut self, max_messages: u32, per: Duration) -> PushoverLayerBuilder {
+        self.max_messages = max_messages;
+        self.per = per;
+        self
+    }
+
+    /// Sets how many events can wait to be sent. (Min: 1)
+    pub fn set_capacity(mut self, capacity: usize) -> PushoverLayerBuilder {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sends the messages through this client (eg: with a custom transport)
+    pub fn set_client(mut self, client: PushoverClient) -> PushoverLayerBuilder {
+        self.client = client;
+        self
+    }
+
+    /// Starts the background thread and returns the layer
+    pub fn build(self) -> PushoverLayer {
+        let (sender, receiver) = mpsc::sync_channel::<Command>(self.capacity);
+        let client: PushoverClient = self.client;
+        std::thread::Builder::new()
+            .name(PUSHOVER_LAYER_THREAD_NAME.into())
+            .spawn(move || forward(client, receiver))
+            .expect("Cannot start the Pushover layer thread");
+
+        PushoverLayer {
+            user_key: self.user_key,
+            app_token: self.app_token,
+            level: self.level,
+            priority: self.priority,
+            rate_limit: Mutex::new(RateLimit {
+                max_messages: self.max_messages,
+                per: self.per,
+                window_start: Instant::now(),
+                sent: 0,
+            }),
+            sender,
+        }
+    }
+}
+
+/// Sends the messages until the layer is dropped. Failures can't be reported anywhere but in the logs, so they are ignored.
+fn forward(client: PushoverClient, receiver: Receiver<Command>) {
+    for command in receiver {
+        match command {
+            Command::Send(message) => {
+                let _ = client.send(&message);
+            },
+            Command::Flush(done) => {
+                let _ = done.send(());
+            },
+        }
+    }
+}
+
+impl RateLimit {
+    fn allow(&mut self) -> bool {
+        let now: Instant = Instant::now();
+        if now.duration_since(self.window_start) >= self.per {
+            self.window_start = now;
+            self.sent = 0;
+        }
+
+        if self.sent >= self.max_messages {
+            return false;
+        }
+
+        self.sent += 1;
+        true
+    }
+}
+
+impl PushoverLayer {
+    /// Waits (up to 10 seconds) for the pending messages to be sent, eg: before exiting
+    pub fn flush(&self) {
+        let (done_sender, done_receiver) = mpsc::sync_channel::<()>(1);
+        if self.sender.send(Command::Flush(done_sender)).is_ok() {
+            let _ = done_receiver.recv_timeout(PUSHOVER_LAYER_FLUSH_TIMEOUT);
+        }
+    }
+
+    /// Converts an event into a message: the target as title, the event's message then its other fields in monospace
+    fn to_message(&self, event: &Event<'_>) -> Message {
+        let mut visitor: FieldVisitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let mut text: String = format!("{} {}", metadata.level(), visitor.message.unwrap_or_default());
+        for (name, value) in visitor.fields {
+            let _ = write!(text, "\n{} = {}", name, value);
+        }
+
+        let mut builder: MessageBuilder = MessageBuilder::new(&self.user_key, &self.app_token, &truncate(&text, PUSHOVER_API_MAX_MESSAGE_LENGTH))
+            .set_title(&truncate(metadata.target(), PUSHOVER_API_MAX_TITLE_LENGTH))
+            .set_monospace(true);
+        if let Some(priority) = &self.priority {
+            builder = builder.set_priority(priority.clone());
+        }
+        builder.build()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for PushoverLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        // Less severe levels are greater
+        if *metadata.level() > self.level {
+            return;
+        }
+
+        // The events of the crate, or of the HTTP stack on the sending thread, would loop through this layer
+        if metadata.target().starts_with(env!("CARGO_CRATE_NAME")) || std::thread::current().name() == Some(PUSHOVER_LAYER_THREAD_NAME) {
+            return;
+        }
+
+        if !self.rate_limit.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).allow() {
+            return;
+        }
+
+        // A full channel means Pushover can't keep up, the event is dropped rather than blocking the caller
+        let _ = self.sender.try_send(Command::Send(Box::new(self.to_message(event))));
+    }
+}
+
+impl std::fmt::Debug for PushoverLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PushoverLayer")
+            .field("level", &self.level)
+            .field("priority", &self.priority)
+            .field("rate_limit", &self.rate_limit)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Collects the fields of an event, apart from its message
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = Some(value.to_owned()),
+            name => self.fields.push((name, value.to_owned())),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message = Some(format!("{:?}", value)),
+            name => self.fields.push((name, format!("{:?}", value))),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+/*!
+The HTTP layer used by `PushoverClient`.
+
+Requests are fully encoded (URL, headers and body bytes) before reaching the transport, so any HTTP stack can carry them.
+reqwest is used by default, see `PushoverClient::set_transport` to plug in another one (or an in-memory fake for tests).
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::PushoverError;
+
+/// The future returned by `Transport::send`
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<TransportResponse, PushoverError>> + Send + 'a>>;
+
+/**
+ Sends encoded requests to the Pushover API and returns the raw responses.
+ **/
+pub trait Transport: Send + Sync {
+    /// Sends the request. HTTP error statuses are not errors, the API describes them in the response body.
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_>;
+}
+
+/**
+ The HTTP method of a request.
+ **/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+// Enables Method::[METHOD].to_string();
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name: &str = match *self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/**
+ An encoded request, ready to be sent.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportRequest {
+    pub method: Method,
+    /// The full URL, query string included
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TransportRequest {
+    /// A POST request with a JSON body
+    pub fn json<T: Serialize + ?Sized>(url: &str, body: &T) -> Result<TransportRequest, PushoverError> {
+        let body: Vec<u8> = serde_json::to_vec(body)
+            .map_err(|error| PushoverError::InvalidInput(format!("Cannot serialize the request: {}", error)))?;
+
+        Ok(TransportRequest {
+            method: Method::Post,
+            url: url.to_owned(),
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body,
+        })
+    }
+
+    /// A POST request with a multipart/form-data body made of text fields and an optional file
+    pub fn multipart(url: &str, fields: &[(&str, String)], file: Option<MultipartFile>) -> TransportRequest {
+        let boundary: String = multipart_boundary();
+        let mut body: Vec<u8> = Vec::new();
+
+        for (name, value) in fields {
+            body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n", boundary, name).as_bytes());
+            body.extend_from_slice(value.as_bytes());
+            body.extend_from_slice(b"\r\n");
+        }
+
+        if let Some(file) = file {
+            body.extend_from_slice(format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                boundary, file.field_name, file.file_name.replace('"', "%22"), file.content_type,
+            ).as_bytes());
+            body.extend_from_slice(&file.bytes);
+            body.extend_from_slice(b"\r\n");
+        }
+
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        TransportRequest {
+            method: Method::Post,
+            url: url.to_owned(),
+            headers: vec![("Content-Type".into(), format!("multipart/form-data; boundary={}", boundary))],
+            body,
+        }
+    }
+
+    /// A GET request, with the given query string parameters
+    pub fn get(url: &str, query: &[(&str, &str)]) -> TransportRequest {
+        let mut url: String = url.to_owned();
+
+        if !query.is_empty() {
+            let query: String = query
+                .iter()
+                .map(|(name, value)| format!("{}={}", percent_encode(name), percent_encode(value)))
+                .collect::<Vec<String>>()
+                .join("&");
+            url = format!("{}?{}", url, query);
+        }
+
+        TransportRequest {
+            method: Method::Get,
+            url,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// The value of a header, looked up case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/**
+ A file sent in a multipart request.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartFile {
+    pub field_name: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/**
+ A raw response, as received by the transport.
+ **/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// The HTTP status code
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    /// Deserializes the JSON body
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, PushoverError> {
+        serde_json::from_slice::<T>(&self.body)
+            .map_err(|error| PushoverError::InvalidResponse(format!("HTTP {}: {}", self.status, error)))
+    }
+
+    /// The value of a header, looked up case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/**
+ The default transport, built on an async reqwest client. Its requests must be awaited from within a tokio runtime.
+ **/
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        ReqwestTransport::default()
+    }
+
+    /// Uses an existing reqwest client, eg: configured with a proxy or timeouts
+    pub fn with_client(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let method: reqwest::Method = match request.method {
+                Method::Get => reqwest::Method::GET,
+                Method::Post => reqwest::Method::POST,
+            };
+            let mut builder: reqwest::RequestBuilder = self.client.request(method, &request.url);
+            for (name, value) in &request.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+
+            let response: reqwest::Response = builder.body(request.body).send().await?;
+            let status: u16 = response.status().as_u16();
+            let headers: Vec<(String, String)> = headers_of(response.headers());
+            let body: Vec<u8> = response.bytes().await?.to_vec();
+
+            Ok(TransportResponse { status, headers, body })
+        })
+    }
+}
+
+/// Copies reqwest's headers, skipping the ones that aren't valid strings
+pub(crate) fn headers_of(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_owned(), value.to_str().ok()?.to_owned())))
+        .collect()
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn multipart_boundary() -> String {
+    let nanos: u128 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("pushover-rs-boundary-{:x}", nanos)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,124 @@
+/*!
+A gateway turning arbitrary JSON webhooks (GitHub, Sentry, Grafana...) into Pushover messages (Requires the `webhook` feature)
+
+Each path has declarative mapping rules (see WebhookRoute), selecting the webhook's fields that make the message.
+The gateway doesn't include an HTTP server: give it the path and body of the requests received by yours
+(see the `webhook-gateway` example for a standalone gateway).
+ */
+
+use serde_json::Value;
+
+pub use crate::pushover::data::{PriorityRule, WebhookGatewayConfig, WebhookRoute};
+use crate::pushover::constants::{PUSHOVER_API_MAX_MESSAGE_LENGTH, PUSHOVER_API_MAX_TITLE_LENGTH};
+use crate::pushover::data::select_text;
+use crate::pushover::text::truncate;
+use crate::{Message, MessageBuilder, PushoverClient, PushoverError, PushoverResponse, PushoverSound};
+
+/**
+Sends the webhooks received on the configured paths as Pushover messages.
+ */
+#[derive(Debug, Clone)]
+pub struct WebhookGateway {
+    config: WebhookGatewayConfig,
+    client: PushoverClient,
+}
+
+impl WebhookGateway {
+    /// Creates a gateway without routes, sending to this user or group
+    pub fn new(user_key: &str, application_token: &str) -> Self {
+        WebhookGateway::from_config(WebhookGatewayConfig {
+            user_key: user_key.to_owned(),
+            app_token: application_token.to_owned(),
+            routes: Vec::new(),
+        })
+    }
+
+    /// Creates a gateway from its configuration
+    pub fn from_config(config: WebhookGatewayConfig) -> Self {
+        Self {
+            config,
+            client: PushoverClient::new(),
+        }
+    }
+
+    /// Reads the configuration from TOML
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<WebhookGateway, PushoverError> {
+        let config: WebhookGatewayConfig = toml::from_str(toml)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid TOML gateway configuration: {}", error)))?;
+        Ok(WebhookGateway::from_config(config))
+    }
+
+    /// Reads the configuration from YAML
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(yaml: &str) -> Result<WebhookGateway, PushoverError> {
+        let config: WebhookGatewayConfig = serde_yaml_ng::from_str(yaml)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid YAML gateway configuration: {}", error)))?;
+        Ok(WebhookGateway::from_config(config))
+    }
+
+    /// Sends the messages through this client
+    pub fn set_client(mut self, client: PushoverClient) -> WebhookGateway {
+        self.client = client;
+        self
+    }
+
+    /// Adds a route, replacing the one on the same path if any
+    pub fn add_route(mut self, route: WebhookRoute) -> WebhookGateway {
+        self.config.routes.retain(|existing| existing.path != route.path);
+        self.config.routes.push(route);
+        self
+    }
+
+    /// The route of a path, if any
+    pub fn route(&self, path: &str) -> Option<&WebhookRoute> {
+        self.config.routes.iter().find(|route| route.path == path)
+    }
+
+    /// Parses the JSON body of a webhook received on a path, and sends its message
+    pub async fn handle(&self, path: &str, body: &[u8]) -> Result<PushoverResponse, PushoverError> {
+        let webhook: Value = serde_json::from_slice(body)
+            .map_err(|error| PushoverError::InvalidInput(format!("Invalid webhook body: {}", error)))?;
+        let message: Message = self.message_for(path, &webhook)?;
+        self.client.send(&message).await
+    }
+
+    /// Builds the message of a webhook received on a path. Fails if there is no such route or the message field is missing.
+    pub fn message_for(&self, path: &str, webhook: &Value) -> Result<Message, PushoverError> {
+        let route: &WebhookRoute = self.route(path)
+            .ok_or_else(|| PushoverError::InvalidInput(format!("No webhook route for {}", path)))?;
+
+        let text: String = select_text(webhook, &route.message)?
+            .ok_or_else(|| PushoverError::InvalidInput(format!("The webhook has no message at {}", route.message)))?;
+        let user_key: &str = route.user_key.as_deref().unwrap_or(&self.config.user_key);
+        let mut builder: MessageBuilder = MessageBuilder::new(user_key, &self.config.app_token, &truncate(&text, PUSHOVER_API_MAX_MESSAGE_LENGTH));
+
+        if let Some(title) = select_optional(webhook, route.title.as_deref())? {
+            builder = builder.set_title(&truncate(&title, PUSHOVER_API_MAX_TITLE_LENGTH));
+        }
+
+        if let Some(url) = select_optional(webhook, route.url.as_deref())? {
+            let url_title: Option<String> = select_optional(webhook, route.url_title.as_deref())?;
+            builder = builder.set_url(&url, url_title.as_deref());
+        }
+
+        if let Some(priority) = &route.priority {
+            builder = builder.set_priority(priority.priority_of(webhook)?);
+        }
+
+        if let Some(sound) = &route.sound {
+            let sound: PushoverSound = sound.parse()
+                .map_err(|_| PushoverError::InvalidInput(format!("Unknown sound: {}", sound)))?;
+            builder = builder.set_sound(sound);
+        }
+
+        Ok(builder.build())
+    }
+}
+
+fn select_optional(webhook: &Value, field: Option<&str>) -> Result<Option<String>, PushoverError> {
+    match field {
+        Some(field) => select_text(webhook, field),
+        None => Ok(None),
+    }
+}